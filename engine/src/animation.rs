@@ -0,0 +1,148 @@
+use crate::sprite::Sprite;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnimationEvent {
+    Finished(String), // a one-shot clip showed its last frame
+    Looped(String),
+}
+
+#[derive(Clone)]
+pub struct Clip {
+    frames: Vec<(Sprite, f64)>, // sprite and how long it is shown for
+    looping: bool,
+    next: Option<String>,
+}
+
+impl Clip {
+    pub fn looping(frames: Vec<(Sprite, f64)>) -> Self {
+        Clip {
+            frames,
+            looping: true,
+            next: None,
+        }
+    }
+
+    pub fn once(frames: Vec<(Sprite, f64)>) -> Self {
+        Clip {
+            frames,
+            looping: false,
+            next: None,
+        }
+    }
+
+    // A single frame that never changes
+    pub fn still(sprite: Sprite) -> Self {
+        Clip::looping(vec![(sprite, f64::INFINITY)])
+    }
+
+    // Clip to switch to once a one-shot clip finishes
+    pub fn then(mut self, next: &str) -> Self {
+        self.next = Some(next.to_string());
+        self
+    }
+}
+
+// Component that holds an entity's clips; the World advances every
+// Animator once per tick before entities update
+#[derive(Clone)]
+pub struct Animator {
+    clips: HashMap<String, Clip>,
+    current: String,
+    frame: usize,
+    elapsed: f64,
+    finished: bool,
+    events: Vec<AnimationEvent>,
+}
+
+impl Animator {
+    // The first clip added starts playing
+    pub fn new(name: &str, clip: Clip) -> Self {
+        let mut clips = HashMap::new();
+        clips.insert(name.to_string(), clip);
+        Animator {
+            clips,
+            current: name.to_string(),
+            frame: 0,
+            elapsed: 0.0,
+            finished: false,
+            events: vec![],
+        }
+    }
+
+    pub fn with_clip(mut self, name: &str, clip: Clip) -> Self {
+        self.clips.insert(name.to_string(), clip);
+        self
+    }
+
+    // Does nothing if the clip is already playing, so it is safe to call
+    // every tick
+    pub fn play(&mut self, name: &str) {
+        if self.current == name && !self.finished {
+            return;
+        }
+        self.restart(name);
+    }
+
+    pub fn restart(&mut self, name: &str) {
+        if self.clips.contains_key(name) {
+            self.current = name.to_string();
+            self.frame = 0;
+            self.elapsed = 0.0;
+            self.finished = false;
+        }
+    }
+
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn sprite(&self) -> Option<&Sprite> {
+        self.clips
+            .get(&self.current)?
+            .frames
+            .get(self.frame)
+            .map(|(x, _)| x)
+    }
+
+    pub fn take_events(&mut self) -> Vec<AnimationEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    // Events only describe the latest tick, read them from Entity::update
+    pub fn advance(&mut self, delta: f64) {
+        self.events.clear();
+        if self.finished {
+            return;
+        }
+        let Some(clip) = self.clips.get(&self.current) else {
+            return;
+        };
+        if clip.frames.iter().all(|(_, x)| *x <= 0.0) {
+            return;
+        }
+        self.elapsed += delta;
+        while self.elapsed >= clip.frames[self.frame].1 {
+            self.elapsed -= clip.frames[self.frame].1;
+            if self.frame + 1 < clip.frames.len() {
+                self.frame += 1;
+            } else if clip.looping {
+                self.frame = 0;
+                self.events
+                    .push(AnimationEvent::Looped(self.current.clone()));
+            } else {
+                self.events
+                    .push(AnimationEvent::Finished(self.current.clone()));
+                match clip.next.clone() {
+                    Some(next) => self.restart(&next),
+                    None => self.finished = true,
+                }
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+// Which part of the map a viewport shows. Positions are in map tiles.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Camera {
+    pub position: (u16, u16), // tile shown in the viewport's top left
+    pub target: Option<i64>,  // entity to keep centred, found by its tiles
+    // First and last tile the camera may show, the whole map if None
+    pub bounds: Option<((u16, u16), (u16, u16))>,
+}
+
+impl Camera {
+    pub fn at(position: (u16, u16)) -> Self {
+        Camera {
+            position,
+            ..Camera::default()
+        }
+    }
+
+    pub fn following(mut self, target: i64) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn bounded(mut self, first: (u16, u16), last: (u16, u16)) -> Self {
+        self.bounds = Some((first, last));
+        self
+    }
+
+    // `target` is where the followed entity was drawn this tick, if
+    // anywhere; the camera stays put while it is off the map
+    pub(crate) fn update(
+        &mut self,
+        target: Option<(u16, u16)>,
+        view: (u16, u16),
+        map: (u16, u16),
+    ) {
+        if let Some(target) = target {
+            self.position = (
+                target.0.saturating_sub(view.0 / 2),
+                target.1.saturating_sub(view.1 / 2),
+            );
+        }
+        let (first, last) = self.bounds.unwrap_or((
+            (0, 0),
+            (map.0.saturating_sub(1), map.1.saturating_sub(1)),
+        ));
+        let clamp = |position: u16, first: u16, last: u16, view: u16| {
+            let furthest = (last + 1).saturating_sub(view).max(first);
+            position.clamp(first, furthest)
+        };
+        self.position = (
+            clamp(self.position.0, first.0, last.0, view.0),
+            clamp(self.position.1, first.1, last.1, view.1),
+        );
+    }
+}
+
+// A rectangle of the terminal that shows the map through a camera. The
+// size is in map tiles, so in half block mode a viewport takes up half as
+// many terminal rows as it is tall.
+#[derive(Clone, Copy, Debug)]
+pub struct Viewport {
+    pub origin: (u16, u16), // terminal cell of the top left corner
+    pub size: (u16, u16),
+    pub camera: Camera,
+    drawn_from: Option<(i32, i32)>, // map tile in the top left last draw
+}
+
+impl Viewport {
+    pub fn new(origin: (u16, u16), size: (u16, u16), camera: Camera) -> Self {
+        Viewport {
+            origin,
+            size,
+            camera,
+            drawn_from: None,
+        }
+    }
+
+    // Everything has to be redrawn after the camera moves or shakes
+    pub(crate) fn moved(&mut self, view: (i32, i32)) -> bool {
+        let moved = self.drawn_from != Some(view);
+        self.drawn_from = Some(view);
+        moved
+    }
+
+    pub(crate) fn redraw(&mut self) {
+        self.drawn_from = None;
+    }
+}
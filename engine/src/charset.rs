@@ -0,0 +1,114 @@
+use std::env;
+
+// Which characters the terminal can show. Anything outside the set is
+// swapped for its fallback just before it is written out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Charset {
+    Ascii,
+    Latin1,
+    Unicode,
+}
+
+// Each glyph the engine or game draws outside of ASCII, with what to show
+// instead on Latin-1 and ASCII terminals
+const FALLBACKS: [(char, char, char); 26] = [
+    ('█', '#', '#'),
+    ('▇', '#', '#'),
+    ('▆', '=', '='),
+    ('▅', '=', '='),
+    ('▃', '-', '-'),
+    ('▂', '_', '_'),
+    ('▁', '_', '_'),
+    ('▓', '#', '#'),
+    ('▒', '%', '%'),
+    ('░', '·', '.'),
+    ('▀', '¯', '"'),
+    ('▄', '_', '_'),
+    ('▪', '·', '.'),
+    ('•', '·', '*'),
+    ('·', '·', '.'),
+    ('✶', '*', '*'),
+    ('✦', '+', '+'),
+    ('×', '×', 'x'),
+    ('°', '°', 'o'),
+    ('─', '-', '-'),
+    ('│', '¦', '|'),
+    ('┌', '+', '+'),
+    ('┐', '+', '+'),
+    ('└', '+', '+'),
+    ('┘', '+', '+'),
+    ('…', '.', '.'),
+];
+
+impl Charset {
+    // The first locale variable that is set decides, like in libc. Only a
+    // UTF-8 locale means the terminal can show everything.
+    pub fn detect() -> Self {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|x| env::var(x).ok())
+            .find(|x| !x.is_empty())
+            .unwrap_or_default()
+            .to_lowercase();
+        if locale.contains("utf-8") || locale.contains("utf8") {
+            Charset::Unicode
+        } else if locale.contains("8859-1")
+            || locale.contains("8859-15")
+            || locale.contains("latin1")
+        {
+            Charset::Latin1
+        } else {
+            Charset::Ascii
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ascii" => Some(Charset::Ascii),
+            "latin1" | "latin-1" => Some(Charset::Latin1),
+            "unicode" | "utf-8" | "utf8" => Some(Charset::Unicode),
+            _ => None,
+        }
+    }
+
+    pub fn supports(&self, character: char) -> bool {
+        match self {
+            Charset::Ascii => character.is_ascii(),
+            Charset::Latin1 => (character as u32) < 0x100,
+            Charset::Unicode => true,
+        }
+    }
+
+    // Characters without a listed fallback become '?'
+    pub fn fallback(&self, character: char) -> char {
+        if self.supports(character) {
+            return character;
+        }
+        FALLBACKS.iter().find(|(x, _, _)| *x == character).map_or(
+            '?',
+            |(_, latin1, ascii)| match self {
+                Charset::Latin1 => *latin1,
+                _ => *ascii,
+            },
+        )
+    }
+
+    pub fn convert(&self, text: &str) -> String {
+        if *self == Charset::Unicode {
+            return text.to_string();
+        }
+        text.chars().map(|x| self.fallback(x)).collect()
+    }
+
+    // The bytes a terminal with this charset expects, one per character on
+    // Latin-1 rather than UTF-8's two for anything past ASCII
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            Charset::Latin1 => text
+                .chars()
+                .map(|x| u8::try_from(self.fallback(x)).unwrap_or(b'?'))
+                .collect(),
+            _ => self.convert(text).into_bytes(),
+        }
+    }
+}
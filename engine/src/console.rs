@@ -0,0 +1,279 @@
+use crate::World;
+use crossterm::{event::KeyCode, style::Color, terminal};
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    rc::Rc,
+};
+
+pub const CONSOLE_LINES: u16 = 9; // output, the prompt and a divider
+const OUTPUT_LINES: usize = CONSOLE_LINES as usize - 2;
+const SCROLLBACK: usize = 100;
+
+type Handler = dyn Fn(&mut World, &[&str]) -> Result<String, String>;
+
+// A command gets the words typed after its name and returns what to print,
+// or an error to print in red
+pub struct Command {
+    name: String,
+    usage: String,
+    handler: Rc<Handler>,
+    completions: Vec<String>,
+}
+
+impl Command {
+    pub fn new(
+        name: &str,
+        usage: &str,
+        handler: impl Fn(&mut World, &[&str]) -> Result<String, String> + 'static,
+    ) -> Self {
+        Command {
+            name: name.to_string(),
+            usage: usage.to_string(),
+            handler: Rc::new(handler),
+            completions: vec![],
+        }
+    }
+
+    // Words tab completes after the command name
+    pub fn completing(mut self, words: &[&str]) -> Self {
+        self.completions = words.iter().map(|x| x.to_string()).collect();
+        self
+    }
+}
+
+enum Action {
+    Nothing,
+    Close,
+    Run(String),
+}
+
+// Drops down over the top of the screen with ` and pauses the game while it
+// is open. Games register their commands, help and clear are built in.
+pub struct Console {
+    pub open: bool,
+    input: String,
+    output: VecDeque<(String, Color)>,
+    history: Vec<String>,
+    recalled: Option<usize>, // index into history while paging with up/down
+    commands: Vec<Command>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            open: false,
+            input: String::new(),
+            output: VecDeque::new(),
+            history: vec![],
+            recalled: None,
+            commands: vec![],
+        }
+    }
+
+    pub fn register(&mut self, command: Command) -> &mut Self {
+        self.commands.retain(|x| x.name != command.name);
+        self.commands.push(command);
+        self
+    }
+
+    pub fn print(&mut self, text: &str, color: Color) {
+        for line in text.lines() {
+            if self.output.len() == SCROLLBACK {
+                self.output.pop_front();
+            }
+            self.output.push_back((line.to_string(), color));
+        }
+    }
+
+    fn key(&mut self, key: KeyCode) -> Action {
+        match key {
+            KeyCode::Esc | KeyCode::Char('`') => return Action::Close,
+            KeyCode::Enter => {
+                let line = std::mem::take(&mut self.input);
+                self.recalled = None;
+                if line.trim().is_empty() {
+                    return Action::Nothing;
+                }
+                if self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                }
+                return Action::Run(line);
+            }
+            KeyCode::Tab => self.complete(),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Up => self.recall(-1),
+            KeyCode::Down => self.recall(1),
+            KeyCode::Char(x) => self.input.push(x),
+            _ => {}
+        }
+        Action::Nothing
+    }
+
+    fn recall(&mut self, step: isize) {
+        let index = match self.recalled {
+            Some(i) => i.checked_add_signed(step),
+            None if step < 0 => self.history.len().checked_sub(1),
+            None => None,
+        };
+        self.recalled = index.filter(|x| *x < self.history.len());
+        self.input = match self.recalled {
+            Some(i) => self.history[i].clone(),
+            None => String::new(),
+        };
+    }
+
+    // Finishes the word being typed as far as every candidate agrees, and
+    // lists them when that is not far enough
+    fn complete(&mut self) {
+        let start = self.input.rfind(' ').map_or(0, |x| x + 1);
+        let word = &self.input[start..];
+        let candidates: Vec<String> = if start == 0 {
+            self.names()
+        } else {
+            let name = self.input.split(' ').next().unwrap_or("");
+            self.commands
+                .iter()
+                .find(|x| x.name == name)
+                .map_or(vec![], |x| x.completions.clone())
+        };
+        let matches: Vec<&String> =
+            candidates.iter().filter(|x| x.starts_with(word)).collect();
+        let Some(first) = matches.first() else {
+            return;
+        };
+        // in bytes, ending on a char boundary of every match
+        let mut common = first.len();
+        for other in matches.iter() {
+            let end = first
+                .char_indices()
+                .zip(other.chars())
+                .find(|((_, x), y)| x != y)
+                .map_or(first.len().min(other.len()), |((i, _), _)| i);
+            common = common.min(end);
+        }
+        let completed = first[..common].to_string();
+        if matches.len() == 1 {
+            self.input = format!("{}{} ", &self.input[..start], completed);
+        } else if completed.len() > word.len() {
+            self.input = format!("{}{}", &self.input[..start], completed);
+        } else {
+            let listed: Vec<&str> =
+                matches.iter().map(|x| x.as_str()).collect();
+            self.print(&listed.join("  "), Color::DarkGrey);
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> =
+            self.commands.iter().map(|x| x.name.clone()).collect();
+        names.extend(["clear".to_string(), "help".to_string()]);
+        names.sort();
+        names
+    }
+
+    fn help(&self) -> String {
+        let mut lines: Vec<&str> =
+            self.commands.iter().map(|x| x.usage.as_str()).collect();
+        lines.sort();
+        lines.extend(["clear", "help"]);
+        lines.join("\n")
+    }
+}
+
+// True while the console is open, when the game does not get a tick.
+// Commands change the world in ways a replay can't repeat, so they are
+// refused while one is recorded or played.
+pub(crate) fn handle(world: &mut World) -> io::Result<bool> {
+    if !world.console.open {
+        if world.ui.current_input != Some(KeyCode::Char('`')) {
+            return Ok(false);
+        }
+        world.console.open = true;
+        world.ui.current_input = None;
+        draw(world)?;
+        return Ok(true);
+    }
+    // typing is faster than the frame rate, so take every key waiting
+    let keys = world.ui.current_input.take().into_iter();
+    for key in keys.chain(world.ui.pending_inputs()) {
+        match world.console.key(key) {
+            Action::Nothing => {}
+            Action::Close => {
+                close(world)?;
+                return Ok(false);
+            }
+            Action::Run(line) => run(world, &line),
+        }
+    }
+    draw(world)?;
+    Ok(true)
+}
+
+fn run(world: &mut World, line: &str) {
+    world.console.print(&format!("> {}", line), Color::White);
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (name, args) = (words[0], &words[1..]);
+    let result = match name {
+        "help" => Ok(world.console.help()),
+        "clear" => {
+            world.console.output.clear();
+            Ok(String::new())
+        }
+        _ if world.replaying() => {
+            Err("commands are off while a replay is recorded or played".into())
+        }
+        _ => match world.console.commands.iter().find(|x| x.name == name) {
+            Some(command) => {
+                log::info!("console: {}", line);
+                let handler = command.handler.clone();
+                handler(world, args)
+            }
+            None => Err(format!("unknown command {}, try help", name)),
+        },
+    };
+    match result {
+        Ok(text) => world.console.print(&text, Color::Grey),
+        Err(text) => world.console.print(&text, Color::Red),
+    }
+}
+
+fn draw(world: &mut World) -> io::Result<()> {
+    let columns = terminal::size().map_or(80, |x| x.0) as usize;
+    let fit = |x: &str| x.chars().take(columns).collect::<String>();
+    let skip = world.console.output.len().saturating_sub(OUTPUT_LINES);
+    let mut lines: Vec<(String, Color)> = world
+        .console
+        .output
+        .iter()
+        .skip(skip)
+        .map(|(text, color)| (fit(text), *color))
+        .collect();
+    lines.resize(OUTPUT_LINES, (String::new(), Color::Grey));
+    // the end of a long line stays in view while typing it
+    let prompt: String = format!("> {}_", world.console.input);
+    let hidden = prompt.chars().count().saturating_sub(columns);
+    lines.push((prompt.chars().skip(hidden).collect(), Color::Yellow));
+    lines.push(("─".repeat(columns), Color::DarkGrey));
+    for (row, (text, color)) in (0..).zip(lines) {
+        world.ui.clear_line(row)?;
+        world.ui.text_draw((0, row), &text, color)?;
+    }
+    world.ui.stdout.flush()
+}
+
+// The map only draws tiles that changed, so everything under the console
+// has to be drawn again
+fn close(world: &mut World) -> io::Result<()> {
+    world.console.open = false;
+    world.console.recalled = None;
+    for row in 0..CONSOLE_LINES {
+        world.ui.clear_line(row)?;
+    }
+    for viewport in world.viewports.iter_mut() {
+        viewport.redraw();
+    }
+    world.ui.stdout.flush()
+}
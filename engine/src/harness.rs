@@ -0,0 +1,118 @@
+use crate::World;
+use crossterm::event::KeyCode;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// Runs a headless World tick by tick with keys pressed on given ticks, for
+// tests that check what ends up on screen. Snapshots are text files of the
+// screen. UPDATE_SNAPSHOTS=1 writes them, new ones included; without it a
+// missing snapshot fails like a wrong one.
+pub struct Harness {
+    pub world: World,
+    keys: BTreeMap<u64, KeyCode>, // by world tick
+    snapshots: PathBuf,
+}
+
+impl Harness {
+    // `world` should come from World::headless
+    pub fn new(world: World) -> Self {
+        Harness {
+            world,
+            keys: BTreeMap::new(),
+            snapshots: PathBuf::from("snapshots"),
+        }
+    }
+
+    // Where snapshot files go, relative to the package when run by cargo
+    pub fn snapshots(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.snapshots = dir.into();
+        self
+    }
+
+    // `ticks` from now, 0 being the next tick run
+    pub fn press(&mut self, ticks: u64, key: KeyCode) -> &mut Self {
+        self.keys.insert(self.world.tick + ticks, key);
+        self
+    }
+
+    // Each tick advances the game by the world's timestep
+    pub fn run(&mut self, ticks: u64) -> &mut Self {
+        let delta = self.world.timestep.unwrap_or(crate::MIN_FRAME_TIME);
+        for _ in 0..ticks {
+            let key = self.keys.remove(&self.world.tick);
+            self.world.ui.current_input = key;
+            if key.is_some() {
+                self.world.ui.last_input = key;
+            }
+            self.world.tick_once(delta);
+        }
+        self.world.ui.current_input = None;
+        self
+    }
+
+    pub fn screen(&self) -> String {
+        self.world
+            .ui
+            .screen
+            .as_ref()
+            .map_or(String::new(), |x| x.text())
+    }
+
+    // Column and row of the first place `text` shows up
+    pub fn find(&self, text: &str) -> Option<(u16, u16)> {
+        self.screen().lines().enumerate().find_map(|(row, line)| {
+            let start = line.find(text)?;
+            Some((line[..start].chars().count() as u16, row as u16))
+        })
+    }
+
+    pub fn assert_snapshot(&self, name: &str) {
+        let path = self.snapshots.join(format!("{}.txt", name));
+        let screen = self.screen();
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            write_snapshot(&path, &screen);
+            return;
+        }
+        let Ok(expected) = fs::read_to_string(&path) else {
+            panic!(
+                "no snapshot {}, run with UPDATE_SNAPSHOTS=1 to write it\n{}",
+                path.display(),
+                screen
+            );
+        };
+        assert!(
+            expected.trim_end() == screen,
+            "screen does not match {}\n{}",
+            path.display(),
+            side_by_side(expected.trim_end(), &screen)
+        );
+    }
+}
+
+fn write_snapshot(path: &Path, screen: &str) {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).expect("snapshot directory can be made");
+    }
+    fs::write(path, format!("{}\n", screen)).expect("snapshot can be written");
+}
+
+// Expected on the left, actual on the right, differing rows marked
+fn side_by_side(expected: &str, actual: &str) -> String {
+    let width = expected.lines().map(|x| x.chars().count()).max();
+    let width = width.unwrap_or(0);
+    let (expected, actual): (Vec<&str>, Vec<&str>) =
+        (expected.lines().collect(), actual.lines().collect());
+    let mut text = String::new();
+    for row in 0..expected.len().max(actual.len()) {
+        let left = expected.get(row).copied().unwrap_or("");
+        let right = actual.get(row).copied().unwrap_or("");
+        let mark = if left == right { ' ' } else { '!' };
+        let padding = width - left.chars().count();
+        text +=
+            &format!("{} {}{} | {}\n", mark, left, " ".repeat(padding), right);
+    }
+    text
+}
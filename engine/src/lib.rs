@@ -1,29 +1,63 @@
+use animation::Animator;
+use camera::{Camera, Viewport};
+use console::Console;
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
     event::KeyCode,
     style::Color,
     terminal, ExecutableCommand,
 };
+use overlay::Overlay;
+use particles::Particles;
+use post::PostEffects;
+use profiler::Profiler;
+use replay::{Playback, Replay};
+use rewind::{Rewind, Step};
+use rng::Rng;
+use save::{Registry, SaveError};
+use sprite::Sprite;
+use style::Style;
 use ui::UI;
 //use space_invaders_macros::Component;
 use std::{
-    any::{Any, TypeId},
+    any::{type_name, Any, TypeId},
     collections::HashMap,
+    fs,
     io::{self, Write},
+    path::{Path, PathBuf},
     thread,
     time::{Duration, Instant},
 };
+pub mod animation;
+pub mod camera;
+pub mod charset;
+pub mod console;
+pub mod harness;
+pub mod logging;
+pub mod overlay;
+pub mod particles;
+pub mod post;
+pub mod profiler;
+pub mod replay;
+mod rewind;
+pub mod rng;
+pub mod save;
+pub mod sprite;
+pub mod style;
 pub mod ui;
 // Drawing too fast causes flickering
 const MIN_FRAME_TIME: f64 = 0.04;
-pub trait Entity {
-    fn start(&mut self, world: &mut World, id: i64) {}
-    fn update(&mut self, delta: f64, world: &mut World, id: i64) {}
+// rows above the rewind status line for the inspected entity
+const INSPECT_LINES: usize = 6;
+pub trait Entity: Any {
+    fn start(&mut self, _world: &mut World, _id: i64) {}
+    fn update(&mut self, _delta: f64, _world: &mut World, _id: i64) {}
 }
 
 pub struct EntityData {
     pub entity: Box<dyn Entity>,
     pub id: i64,
+    pub type_name: &'static str,
     started: bool,
 }
 
@@ -32,53 +66,272 @@ pub struct World {
     removal_queue: Vec<i64>,
     pub map: Map,
     pub ui: UI,
+    pub rng: Rng,
+    pub particles: Particles,
+    pub post: PostEffects,
+    pub render_mode: RenderMode,
+    pub viewports: Vec<Viewport>,
+    pub tick: u64,
+    // when set, every tick advances the game by exactly this many seconds
+    pub timestep: Option<f64>,
+    recording: Option<(Replay, PathBuf)>,
+    playback: Option<Playback>,
+    pub registry: Registry,
+    // F5 saves here and F9 loads it
+    pub quicksave: Option<PathBuf>,
+    rewind: Option<Rewind>,
+    pub overlay: Overlay,
+    pub console: Console,
+    pub profiler: Profiler,
     next_id: i64,
     components: HashMap<i64, HashMap<String, Box<dyn Any>>>,
+    component_names: HashMap<String, &'static str>, // for the overlay
+    // one of each type, for state the whole game reads. Not saved.
+    resources: HashMap<TypeId, Box<dyn Any>>,
 }
 
 impl World {
     pub fn new(map_width: usize, map_height: usize) -> Self {
+        World::with_ui(map_width, map_height, UI::new())
+    }
+
+    // Draws into `ui.screen` instead of the terminal, with a fixed seed and
+    // timestep so every run goes the same way. See harness::Harness.
+    pub fn headless(map_width: usize, map_height: usize) -> Self {
+        let mut world = World::with_ui(map_width, map_height, UI::headless());
+        world.rng = Rng::new(0);
+        world.timestep = Some(MIN_FRAME_TIME);
+        world
+    }
+
+    fn with_ui(map_width: usize, map_height: usize, ui: UI) -> Self {
         World {
             entities: Vec::new(),
             map: Map::new(map_width, map_height),
-            ui: UI::new(),
+            ui,
+            rng: Rng::from_time(),
+            particles: Particles::new(),
+            post: PostEffects::new(),
+            render_mode: RenderMode::Characters,
+            viewports: vec![Viewport::new(
+                (0, 0),
+                (map_width as u16, map_height as u16),
+                Camera::default(),
+            )],
+            tick: 0,
+            timestep: None,
+            recording: None,
+            playback: None,
+            registry: Registry::new(),
+            quicksave: None,
+            rewind: None,
+            overlay: Overlay::new(),
+            console: Console::new(),
+            profiler: Profiler::new(),
             next_id: 0,
             removal_queue: vec![],
             components: HashMap::new(),
+            component_names: HashMap::new(),
+            resources: HashMap::new(),
         }
     }
 
-    pub fn add_entity(&mut self, entity_data: impl Entity + 'static) {
+    pub fn add_entity<T: Entity>(&mut self, entity_data: T) -> i64 {
+        let id = self.next_id;
         self.entities.push(EntityData {
             entity: Box::new(entity_data),
-            id: self.next_id,
+            id,
+            type_name: short_type_name::<T>(),
             started: false,
         });
-        self.components.insert(self.next_id, HashMap::new());
+        log::trace!("added #{} {}", id, short_type_name::<T>());
+        self.components.insert(id, HashMap::new());
         self.next_id += 1;
+        id
     }
 
     pub fn remove_entity(&mut self, id: i64) {
+        log::trace!("removing #{}", id);
         self.removal_queue.push(id);
     }
 
+    // false once removal has been requested, even if the entity is
+    // still in the list until the next tick
+    pub fn is_alive(&self, id: i64) -> bool {
+        self.components.contains_key(&id) && !self.removal_queue.contains(&id)
+    }
+
+    // Terminal cells covered by all viewports, counted from the top left
+    // corner of the terminal
+    pub fn screen_size(&self) -> (u16, u16) {
+        self.viewports.iter().fold((0, 0), |size, x| {
+            let rows = self.screen_rows(x.size.1);
+            (
+                size.0.max(x.origin.0 + x.size.0),
+                size.1.max(x.origin.1 + rows),
+            )
+        })
+    }
+
+    // Writes the seed, `args` and the input of every tick to `path` when the
+    // game quits. Replays need a fixed timestep, so this sets one unless
+    // there already is one. Call it before the first tick.
+    pub fn record(&mut self, path: impl Into<PathBuf>, args: Vec<String>) {
+        let timestep = *self.timestep.get_or_insert(MIN_FRAME_TIME);
+        let replay = Replay::new(self.rng.seed(), timestep, args);
+        log::info!("recording with seed {}", replay.seed);
+        self.recording = Some((replay, path.into()));
+    }
+
+    // The replay being recorded, as far as it has got
+    pub fn recorded(&self) -> Option<&Replay> {
+        self.recording.as_ref().map(|(replay, _)| replay)
+    }
+
+    // Feeds the replay's input to the game instead of the keyboard's. The
+    // world has to be set up the way it was when the replay was recorded.
+    pub fn play(&mut self, replay: Replay) {
+        log::info!(
+            "playing {} ticks from seed {}",
+            replay.ticks(),
+            replay.seed
+        );
+        self.rng = Rng::new(replay.seed);
+        self.timestep = Some(replay.timestep);
+        self.playback = Some(Playback {
+            replay,
+            paused: false,
+            fast_forward: false,
+        });
+    }
+
+    // While a replay is recorded or played, anything that would make the
+    // game go differently next time has to wait
+    pub fn replaying(&self) -> bool {
+        self.recording.is_some() || self.playback.is_some()
+    }
+
+    // Keeps a snapshot of every tick for the last `seconds`, to step back
+    // and forth through while paused with p. Entities the registry does not
+    // know are lost when stepping, and replays can't be rewound.
+    pub fn enable_rewind(&mut self, seconds: f64) {
+        let tick = self.timestep.unwrap_or(MIN_FRAME_TIME);
+        self.rewind = Some(Rewind::new((seconds / tick).ceil() as usize));
+    }
+
+    // The state of every entity whose type is in the registry, as JSON
+    pub fn snapshot(&self) -> Result<String, SaveError> {
+        save::snapshot(self, true)
+    }
+
+    // Replaces all entities with the ones in a snapshot. Only call this
+    // between ticks, entities that are updating are not in the list.
+    pub fn restore(&mut self, snapshot: &str) -> Result<(), SaveError> {
+        save::restore(self, snapshot)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        Ok(fs::write(path, self.snapshot()?)?)
+    }
+
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        self.restore(&fs::read_to_string(path)?)
+    }
+
+    fn screen_rows(&self, height: u16) -> u16 {
+        match self.render_mode {
+            RenderMode::Characters => height,
+            RenderMode::HalfBlock => height.div_ceil(2),
+        }
+    }
+
     fn draw(&mut self) {
-        let map = &self.map;
-        for c in 0..map.width {
-            for r in 0..map.height {
-                if !map.tiles[c][r].current_contents.is_empty()
-                    || !map.tiles[c][r].previous_contents.is_empty()
-                {
-                    let _ = self.ui.terminal_draw(
-                        (c as u16, r as u16),
-                        map.tiles[c][r].display_character,
-                        map.tiles[c][r].color,
-                    );
+        let map_size = self.map.size();
+        let recolor = self.post.needs_redraw();
+        for i in 0..self.viewports.len() {
+            let target = self.viewports[i]
+                .camera
+                .target
+                .and_then(|x| self.map.locate(x));
+            let viewport = &mut self.viewports[i];
+            viewport.camera.update(target, viewport.size, map_size);
+            let offset = self.post.offset();
+            let view = (
+                viewport.camera.position.0 as i32 + offset.0,
+                viewport.camera.position.1 as i32 + offset.1,
+            );
+            let redraw = viewport.moved(view) || recolor;
+            let viewport = *viewport;
+            for c in 0..viewport.size.0 {
+                for r in 0..self.screen_rows(viewport.size.1) {
+                    let column = view.0 + c as i32;
+                    let cell = match self.render_mode {
+                        RenderMode::Characters => {
+                            self.glyph((column, view.1 + r as i32), redraw)
+                        }
+                        RenderMode::HalfBlock => self.half_block(
+                            (column, view.1 + r as i32 * 2),
+                            redraw,
+                            viewport.size.1 > r * 2 + 1,
+                        ),
+                    };
+                    if let Some((character, style)) = cell {
+                        let _ = self.ui.terminal_draw(
+                            (viewport.origin.0 + c, viewport.origin.1 + r),
+                            character,
+                            self.post.apply(style),
+                        );
+                    }
                 }
             }
         }
     }
 
+    // None when the tile looks the same as last tick
+    fn glyph(
+        &self,
+        position: (i32, i32),
+        redraw: bool,
+    ) -> Option<(char, Style)> {
+        let tile = self.map.tile(position);
+        if !redraw && !tile.is_some_and(|x| x.changed()) {
+            return None;
+        }
+        Some(
+            tile.and_then(|x| x.top())
+                .map_or((' ', Style::default()), |x| (x.character, x.style)),
+        )
+    }
+
+    // Every terminal cell shows two map tiles stacked on top of each other,
+    // the upper one as the foreground of '▀' and the lower one as its
+    // background. Characters are dropped, only colors are kept.
+    fn half_block(
+        &self,
+        position: (i32, i32),
+        redraw: bool,
+        has_lower: bool,
+    ) -> Option<(char, Style)> {
+        let upper = self.map.tile(position);
+        let lower = if has_lower {
+            self.map.tile((position.0, position.1 + 1))
+        } else {
+            None
+        };
+        let changed = |x: Option<&MapTile>| x.is_some_and(|x| x.changed());
+        if !redraw && !changed(upper) && !changed(lower) {
+            return None;
+        }
+        let pixel =
+            |x: Option<&MapTile>| x.and_then(|x| x.top()).map(|x| x.pixel());
+        Some(match (pixel(upper), pixel(lower)) {
+            (None, None) => (' ', Style::default()),
+            (Some(x), None) => ('▀', Style::from(x)),
+            (None, Some(x)) => ('▄', Style::from(x)),
+            (Some(x), Some(y)) => ('▀', Style::from(x).on(y)),
+        })
+    }
+
     pub fn query_map(&mut self, position: (u16, u16)) -> Vec<&mut EntityData> {
         let mut world_entities = Vec::new();
         for entity in self.entities.iter_mut() {
@@ -109,20 +362,44 @@ impl World {
 
         let _ = terminal::disable_raw_mode();
 
-        Ok(())
+        let trace = self.profiler.save_trace();
+        let replay = match &self.recording {
+            Some((replay, path)) => {
+                log::info!(
+                    "saving replay of {} ticks to {}",
+                    replay.ticks(),
+                    path.display()
+                );
+                replay.save(path)
+            }
+            None => Ok(()),
+        };
+        trace.and(replay)
     }
 
     fn game_loop(&mut self) -> io::Result<()> {
         let mut now = Instant::now();
         let mut delta: f64;
+        let frame_time = self.timestep.unwrap_or(0.0).max(MIN_FRAME_TIME);
         loop {
             delta = now.elapsed().as_secs_f64();
-            if delta < MIN_FRAME_TIME {
-                thread::sleep(Duration::from_secs_f64(MIN_FRAME_TIME - delta));
+            if delta < frame_time {
+                thread::sleep(Duration::from_secs_f64(frame_time - delta));
                 delta = now.elapsed().as_secs_f64();
             }
             now = Instant::now();
+            self.overlay.frame(delta);
+            // a slow frame must not change what happens in a replay
+            if let Some(timestep) = self.timestep {
+                delta = timestep;
+            }
+            let input = Instant::now();
             self.ui.update_input();
+            if console::handle(self)? {
+                self.profiler.span("input", input);
+                self.profiler.end_frame();
+                continue;
+            }
             if self
                 .ui
                 .current_input
@@ -130,7 +407,35 @@ impl World {
             {
                 break;
             }
-            self.update_entities(delta);
+            self.quicksave_keys()?;
+            let ids: Vec<i64> = self.entities.iter().map(|x| x.id).collect();
+            if self.overlay.handle(self.ui.current_input, &ids) {
+                self.ui.current_input = None;
+            }
+            if self.rewind_keys(delta)? {
+                self.ui.current_input = None;
+                self.profiler.span("input", input);
+                self.profiler.end_frame();
+                continue;
+            }
+            let ticks = match self.playback.as_mut() {
+                Some(playback) => {
+                    playback.ticks(self.ui.current_input.take(), self.tick)
+                }
+                None => 1,
+            };
+            self.profiler.span("input", input);
+            for _ in 0..ticks {
+                self.tick_once(delta);
+            }
+            self.profiler.end_frame();
+            overlay::draw(self)?;
+            if let Some(playback) = &self.playback {
+                let (_, rows) = terminal::size()?;
+                let status = playback.status(self.tick);
+                self.ui.debug_draw(rows.saturating_sub(1), &status)?;
+                _ = self.ui.stdout.flush();
+            }
             self.ui.current_input = None;
         }
 
@@ -144,28 +449,211 @@ impl World {
         Ok(())
     }
 
+    // Loading while a replay is recorded or played would throw it off, so
+    // only saving works then
+    fn quicksave_keys(&mut self) -> io::Result<()> {
+        let Some(path) = self.quicksave.clone() else {
+            return Ok(());
+        };
+        let message = match self.ui.current_input {
+            Some(KeyCode::F(5)) => match self.save(&path) {
+                Ok(()) => format!("Saved to {}", path.display()),
+                Err(error) => error.to_string(),
+            },
+            Some(KeyCode::F(9)) if !self.replaying() => {
+                match self.load(&path) {
+                    Ok(()) => format!("Loaded {}", path.display()),
+                    Err(error) => error.to_string(),
+                }
+            }
+            _ => return Ok(()),
+        };
+        log::info!("{}", message);
+        self.ui.current_input = None;
+        let (_, rows) = terminal::size()?;
+        self.ui.debug_draw(rows.saturating_sub(1), &message)
+    }
+
+    // True while paused, when the game does not get a tick
+    fn rewind_keys(&mut self, delta: f64) -> io::Result<bool> {
+        let replaying = self.replaying();
+        let Some(rewind) = self.rewind.as_mut().filter(|_| !replaying) else {
+            return Ok(false);
+        };
+        let key = self.ui.current_input;
+        if key == Some(KeyCode::Char('p')) {
+            if rewind.paused() {
+                rewind.resume();
+                log::debug!("resumed from the rewind buffer");
+                self.ui.current_input = None;
+                self.draw_rewind()?;
+                return Ok(false);
+            }
+            rewind.pause();
+            log::debug!("paused for rewinding");
+        }
+        if !rewind.paused() {
+            return Ok(false);
+        }
+        let step = match key {
+            Some(KeyCode::Left) => rewind.back(),
+            Some(KeyCode::Right) => rewind.forward(),
+            Some(KeyCode::Char('i')) => {
+                rewind.inspect_next();
+                Step::Nothing
+            }
+            _ => Step::Nothing,
+        };
+        match step {
+            Step::Show(index) => self.show_frame(index),
+            Step::Simulate => {
+                self.ui.current_input = None;
+                self.tick_once(delta);
+                if let Some(rewind) = self.rewind.as_mut() {
+                    rewind.pause();
+                }
+            }
+            Step::Nothing => {}
+        }
+        self.draw_rewind()?;
+        Ok(true)
+    }
+
+    fn show_frame(&mut self, index: usize) {
+        let Some((snapshot, input, delta)) =
+            self.rewind.as_ref().and_then(|x| x.frame(index)).map(
+                |(snapshot, input, delta)| (snapshot.to_string(), input, delta),
+            )
+        else {
+            return;
+        };
+        if save::restore(self, &snapshot).is_ok() {
+            self.ui.current_input = input;
+            self.update_entities(delta);
+            self.tick += 1;
+        }
+    }
+
+    // The status line and the inspected entity at the bottom of the
+    // terminal, or blank rows once the game runs again
+    fn draw_rewind(&mut self) -> io::Result<()> {
+        let Some(rewind) = &self.rewind else {
+            return Ok(());
+        };
+        let (columns, rows) = terminal::size()?;
+        let mut lines = vec![];
+        if rewind.paused() {
+            lines = rewind.inspection();
+            lines.truncate(INSPECT_LINES);
+            lines.push(rewind.status());
+        }
+        let top = rows.saturating_sub(INSPECT_LINES as u16 + 1);
+        let first = rows.saturating_sub(lines.len() as u16);
+        for row in top..first {
+            self.ui.clear_line(row)?;
+        }
+        for (row, line) in (first..rows).zip(lines) {
+            let line: String = line.chars().take(columns as usize).collect();
+            self.ui.debug_draw(row, &line)?;
+        }
+        self.ui.stdout.flush()
+    }
+
+    fn tick_once(&mut self, delta: f64) {
+        if let Some(playback) = &self.playback {
+            self.ui.current_input = playback.replay.input(self.tick);
+            if self.ui.current_input.is_some() {
+                self.ui.last_input = self.ui.current_input;
+            }
+        }
+        if let Some((replay, _)) = self.recording.as_mut() {
+            replay.record(self.tick, self.ui.current_input);
+        }
+        if self.rewind.is_some() {
+            let snapshot = save::snapshot(self, false);
+            match (self.rewind.as_mut(), snapshot) {
+                (Some(rewind), Ok(snapshot)) => {
+                    rewind.push(snapshot, self.ui.current_input, delta)
+                }
+                (_, Err(error)) => log::warn!("no rewind frame: {}", error),
+                _ => {}
+            }
+        }
+        self.update_entities(delta);
+        self.tick += 1;
+    }
+
     fn update_entities(&mut self, delta: f64) {
+        let tick = Instant::now();
+        logging::set_tick(self.tick);
         if !self.removal_queue.is_empty() {
             self.entities
                 .retain(|x| !self.removal_queue.contains(&x.id));
+            for id in self.removal_queue.iter() {
+                self.components.remove(id);
+            }
             self.removal_queue.clear();
         }
+        self.advance_animators(delta);
         let entity_count = self.entities.len();
         let mut current_entity;
         for _i in 0..entity_count {
             current_entity = self.entities.remove(0);
+            let update = Instant::now();
             if !current_entity.started {
                 current_entity.entity.start(self, current_entity.id);
                 current_entity.started = true;
             }
             current_entity.entity.update(delta, self, current_entity.id);
+            self.profiler.span(current_entity.type_name, update);
             self.entities.push(current_entity);
         }
+        let particles = Instant::now();
+        self.particles.update(delta, &mut self.rng);
+        self.particles.draw(&mut self.map);
+        self.post.update(delta, &mut self.rng);
+        self.profiler.span("particles", particles);
 
+        let draw = Instant::now();
         self.draw();
+        self.profiler.span("draw", draw);
+        let flush = Instant::now();
         _ = self.ui.stdout.flush();
+        self.profiler.span("flush", flush);
         self.map.clear();
+        self.profiler.span("tick", tick);
+    }
+    fn advance_animators(&mut self, delta: f64) {
+        let type_string = format!("{:?}", TypeId::of::<Animator>()).to_string();
+        for components in self.components.values_mut() {
+            if let Some(animator) = components
+                .get_mut(&type_string)
+                .and_then(|x| x.downcast_mut::<Animator>())
+            {
+                animator.advance(delta);
+            }
+        }
     }
+
+    // Draws the current frame of the entity's Animator, if it has one
+    pub fn draw_animator(
+        &mut self,
+        id: i64,
+        position: (u16, u16),
+        layer: Layer,
+    ) {
+        let type_string = format!("{:?}", TypeId::of::<Animator>()).to_string();
+        let sprite = self
+            .components
+            .get(&id)
+            .and_then(|x| x.get(&type_string))
+            .and_then(|x| x.downcast_ref::<Animator>())
+            .and_then(|x| x.sprite());
+        if let Some(sprite) = sprite {
+            self.map.draw_sprite(position, sprite, layer, id);
+        }
+    }
+
     pub fn get_component<T: 'static>(&mut self, id: i64) -> Option<&mut T> {
         match self.components.get_mut(&id) {
             Some(x) => {
@@ -181,16 +669,79 @@ impl World {
         }
     }
 
+    pub fn with_component<T: 'static>(&self) -> Vec<i64> {
+        let type_string = format!("{:?}", TypeId::of::<T>()).to_string();
+        let mut ids: Vec<i64> = self
+            .components
+            .iter()
+            .filter(|(id, x)| {
+                x.contains_key(&type_string) && !self.removal_queue.contains(id)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort();
+        ids
+    }
+
     pub fn set_component<T: 'static>(&mut self, id: i64, component: T) {
         match self.components.get_mut(&id) {
             Some(x) => {
                 let type_string =
                     format!("{:?}", TypeId::of::<T>()).to_string();
+                self.component_names
+                    .insert(type_string.clone(), short_type_name::<T>());
                 x.insert(type_string, Box::new(component));
             }
             None => {}
         }
     }
+
+    pub fn remove_component<T: 'static>(&mut self, id: i64) {
+        if let Some(x) = self.components.get_mut(&id) {
+            x.remove(&format!("{:?}", TypeId::of::<T>()));
+        }
+    }
+
+    pub fn resource<T: 'static>(&self) -> Option<&T> {
+        self.resources.get(&TypeId::of::<T>())?.downcast_ref::<T>()
+    }
+
+    pub fn resource_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.resources
+            .get_mut(&TypeId::of::<T>())?
+            .downcast_mut::<T>()
+    }
+
+    pub fn set_resource<T: 'static>(&mut self, resource: T) {
+        self.resources.insert(TypeId::of::<T>(), Box::new(resource));
+    }
+}
+
+// Without the module path, which only gets in the way on screen
+pub(crate) fn short_type_name<T>() -> &'static str {
+    let name = type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+// HalfBlock packs two map rows into every terminal row, so the map is
+// addressed in pixels and should be made twice as tall
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    Characters,
+    HalfBlock,
+}
+
+// Back to front. A tile shows the highest layer written to it; within a
+// layer the entity with the higher id wins, and effects painted without an
+// id go on top in the order they were painted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layer {
+    Background,
+    Terrain,
+    Actors,
+    Projectiles,
+    Effects,
+    Hud,
 }
 
 pub struct Map {
@@ -207,10 +758,10 @@ impl Map {
             tiles: vec![
                 vec![
                     MapTile {
-                        display_character: '#',
-                        color: Color::Black,
+                        cells: Vec::new(),
+                        drawn: false,
                         current_contents: Vec::new(),
-                        previous_contents: Vec::new()
+                        previous_contents: Vec::new(),
                     };
                     height
                 ];
@@ -222,8 +773,8 @@ impl Map {
     pub fn clear(&mut self) {
         for col in self.tiles.iter_mut() {
             for tile in col.iter_mut() {
-                tile.display_character = ' ';
-                tile.color = crossterm::style::Color::Black;
+                tile.drawn = !tile.cells.is_empty();
+                tile.cells.clear();
                 tile.previous_contents.clear();
                 tile.previous_contents.append(&mut tile.current_contents);
             }
@@ -234,23 +785,137 @@ impl Map {
         &mut self,
         position: (u16, u16),
         character: char,
-        color: Color,
+        style: impl Into<Style>,
+        layer: Layer,
         id: i64,
     ) {
         let mut position = position;
         position.0 = position.0.clamp(0, self.width as u16 - 1);
         position.1 = position.1.clamp(0, self.height as u16 - 1);
         let pos = &mut self.tiles[position.0 as usize][position.1 as usize];
-        pos.display_character = character;
-        pos.color = color;
+        pos.cells.push(Cell {
+            layer,
+            order: id,
+            character,
+            style: style.into(),
+        });
         pos.current_contents.push(id);
     }
+
+    // Draws without taking part in collisions, used for particles and
+    // other effects
+    pub fn paint(
+        &mut self,
+        position: (u16, u16),
+        character: char,
+        style: impl Into<Style>,
+        layer: Layer,
+    ) {
+        if (position.0 as usize) < self.width
+            && (position.1 as usize) < self.height
+        {
+            self.tiles[position.0 as usize][position.1 as usize]
+                .cells
+                .push(Cell {
+                    layer,
+                    order: i64::MAX,
+                    character,
+                    style: style.into(),
+                });
+        }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        (self.width as u16, self.height as u16)
+    }
+
+    // None off the map, including left of and above it
+    fn tile(&self, position: (i32, i32)) -> Option<&MapTile> {
+        let column = usize::try_from(position.0).ok()?;
+        let row = usize::try_from(position.1).ok()?;
+        self.tiles.get(column).and_then(|x| x.get(row))
+    }
+
+    // Centre of everything the entity wrote this tick
+    fn locate(&self, id: i64) -> Option<(u16, u16)> {
+        self.centre(|x| x.current_contents.contains(&id))
+    }
+
+    // The same for last tick, once the map has been cleared
+    fn locate_previous(&self, id: i64) -> Option<(u16, u16)> {
+        self.centre(|x| x.previous_contents.contains(&id))
+    }
+
+    fn centre(&self, holds: impl Fn(&MapTile) -> bool) -> Option<(u16, u16)> {
+        let mut found = vec![];
+        for (c, column) in self.tiles.iter().enumerate() {
+            for (r, tile) in column.iter().enumerate() {
+                if holds(tile) {
+                    found.push((c, r));
+                }
+            }
+        }
+        if found.is_empty() {
+            return None;
+        }
+        let sum = found
+            .iter()
+            .fold((0, 0), |sum, x| (sum.0 + x.0, sum.1 + x.1));
+        Some(((sum.0 / found.len()) as u16, (sum.1 / found.len()) as u16))
+    }
+
+    // Unlike write, cells that fall off the map are skipped rather than
+    // clamped to the edge
+    pub fn draw_sprite(
+        &mut self,
+        position: (u16, u16),
+        sprite: &Sprite,
+        layer: Layer,
+        id: i64,
+    ) {
+        for (cell, character, style) in sprite.placed(position) {
+            if (cell.0 as usize) < self.width && (cell.1 as usize) < self.height
+            {
+                self.write(cell, character, style, layer, id);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Cell {
+    layer: Layer,
+    order: i64,
+    character: char,
+    style: Style,
 }
 
 #[derive(Clone)]
 pub struct MapTile {
-    display_character: char,
-    color: Color,
+    cells: Vec<Cell>,           // everything drawn here this tick
+    drawn: bool,                // whether anything was drawn here last tick
     current_contents: Vec<i64>, // by ids
     previous_contents: Vec<i64>,
 }
+
+impl Cell {
+    // A cell drawn in the terminal's default color still has to show up
+    fn pixel(&self) -> Color {
+        match self.style.foreground {
+            Color::Reset => Color::White,
+            x => x,
+        }
+    }
+}
+
+impl MapTile {
+    fn changed(&self) -> bool {
+        !self.cells.is_empty() || self.drawn
+    }
+
+    fn top(&self) -> Option<&Cell> {
+        // max_by_key keeps the last of equal keys, so ties go to the later
+        // write
+        self.cells.iter().max_by_key(|x| (x.layer, x.order))
+    }
+}
@@ -0,0 +1,152 @@
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use std::{
+    error::Error,
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, LineWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// The World's tick, for stamping records logged from anywhere
+static TICK: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn set_tick(tick: u64) {
+    TICK.store(tick, Ordering::Relaxed);
+}
+
+#[derive(Debug)]
+pub enum LogError {
+    Io(io::Error),
+    Filter(String),
+    AlreadySet,
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::Io(x) => write!(f, "could not open log: {}", x),
+            LogError::Filter(x) => write!(f, "bad log filter: {}", x),
+            LogError::AlreadySet => write!(f, "a logger is already set"),
+        }
+    }
+}
+
+impl Error for LogError {}
+
+impl From<io::Error> for LogError {
+    fn from(error: io::Error) -> Self {
+        LogError::Io(error)
+    }
+}
+
+impl From<SetLoggerError> for LogError {
+    fn from(_: SetLoggerError) -> Self {
+        LogError::AlreadySet
+    }
+}
+
+// Appends one line per record to a file, so logging never gets in the way
+// of what is on screen:
+// 14:02:07.113 tick 512 INFO space_invaders::wave: wave 2 with 6 enemies
+pub struct FileLogger {
+    file: Mutex<LineWriter<File>>,
+    level: LevelFilter,
+    targets: Vec<(String, LevelFilter)>, // longest prefix wins
+}
+
+impl FileLogger {
+    // `filter` is a level for everything, then target=level pairs for
+    // modules that should say more or less, like "warn,engine::save=debug"
+    pub fn new(path: impl AsRef<Path>, filter: &str) -> Result<Self, LogError> {
+        let mut level = LevelFilter::Info;
+        let mut targets = vec![];
+        for part in filter.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+            let parse = |x: &str| {
+                x.parse::<LevelFilter>().map_err(|_| {
+                    LogError::Filter(format!("unknown level {}", x))
+                })
+            };
+            match part.split_once('=') {
+                Some((target, x)) => {
+                    targets.push((target.to_string(), parse(x)?))
+                }
+                None => level = parse(part)?,
+            }
+        }
+        targets.sort_by_key(|(x, _)| std::cmp::Reverse(x.len()));
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(FileLogger {
+            file: Mutex::new(LineWriter::new(file)),
+            level,
+            targets,
+        })
+    }
+
+    pub fn install(self) -> Result<(), LogError> {
+        let most = self
+            .targets
+            .iter()
+            .map(|(_, x)| *x)
+            .fold(self.level, Ord::max);
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(most);
+        Ok(())
+    }
+
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(x, _)| target.starts_with(x.as_str()))
+            .map_or(self.level, |(_, level)| *level)
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level_for(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        let _ = writeln!(
+            file,
+            "{} tick {} {} {}: {}",
+            timestamp(),
+            TICK.load(Ordering::Relaxed),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+// Time of day in UTC, to the millisecond
+fn timestamp() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let seconds = now.as_secs() % 86400;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        now.subsec_millis()
+    )
+}
@@ -0,0 +1,175 @@
+use crate::World;
+use crossterm::{event::KeyCode, terminal};
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+};
+
+pub const OVERLAY_LINES: u16 = 7;
+const GRAPH_WIDTH: usize = 40; // frames
+const GRAPH_CEILING: f64 = 0.1; // seconds, a full bar
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+// Frame timings, where the time goes and an entity inspector, drawn `line`
+// rows below the viewports. F3 shows and hides it, [ and ] pick the
+// inspected entity.
+pub struct Overlay {
+    pub visible: bool,
+    pub line: u16,
+    frame_times: VecDeque<f64>, // real seconds, newest last
+    inspected: Option<i64>,     // entity id
+    shown: bool,                // whether it is on screen, to clear it once
+}
+
+impl Overlay {
+    pub fn new() -> Self {
+        Overlay {
+            visible: false,
+            line: 0,
+            frame_times: VecDeque::new(),
+            inspected: None,
+            shown: false,
+        }
+    }
+
+    pub(crate) fn frame(&mut self, seconds: f64) {
+        if self.frame_times.len() == GRAPH_WIDTH {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(seconds);
+    }
+
+    // Whether the key was meant for the overlay
+    pub(crate) fn handle(&mut self, key: Option<KeyCode>, ids: &[i64]) -> bool {
+        let step = match key {
+            Some(KeyCode::F(3)) => {
+                self.visible = !self.visible;
+                return true;
+            }
+            Some(KeyCode::Char(']')) if self.visible => 1,
+            Some(KeyCode::Char('[')) if self.visible => ids.len().max(1) - 1,
+            _ => return false,
+        };
+        let current = ids.iter().position(|x| Some(*x) == self.inspected);
+        self.inspected = match current {
+            Some(i) => ids.get((i + step) % ids.len()).copied(),
+            None => ids.first().copied(),
+        };
+        true
+    }
+
+    fn timing(&self) -> String {
+        let average = self.frame_times.iter().sum::<f64>()
+            / self.frame_times.len().max(1) as f64;
+        let fps = if average > 0.0 { 1.0 / average } else { 0.0 };
+        format!("FPS {:.1}  frame {:.1}ms", fps, average * 1000.0)
+    }
+
+    fn graph(&self) -> String {
+        self.frame_times
+            .iter()
+            .map(|x| {
+                let level = (x / GRAPH_CEILING * BARS.len() as f64) as usize;
+                BARS[level.min(BARS.len() - 1)]
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn draw(world: &mut World) -> io::Result<()> {
+    let line = world.screen_size().1 + world.overlay.line;
+    if !world.overlay.visible {
+        if world.overlay.shown {
+            for row in line..line + OVERLAY_LINES {
+                world.ui.clear_line(row)?;
+            }
+            world.overlay.shown = false;
+            world.ui.stdout.flush()?;
+        }
+        return Ok(());
+    }
+    world.overlay.shown = true;
+
+    let count = world.entities.len();
+    // the first entity until one is picked, or once the picked one is gone
+    let index = world
+        .entities
+        .iter()
+        .position(|x| Some(x.id) == world.overlay.inspected)
+        .unwrap_or(0);
+    world.overlay.inspected = world.entities.get(index).map(|x| x.id);
+    let (entity, components) = match world.entities.get(index) {
+        Some(data) => {
+            let position = world
+                .map
+                .locate_previous(data.id)
+                .map_or("off the map".to_string(), |x| format!("at {:?}", x));
+            let entity = format!(
+                "[{}/{}] #{} {} {}",
+                index + 1,
+                count,
+                data.id,
+                data.type_name,
+                position
+            );
+            (entity, describe_components(world, data.id))
+        }
+        None => ("no entities".to_string(), String::new()),
+    };
+    let lines = [
+        format!(
+            "{}  entities {}  tick {}",
+            world.overlay.timing(),
+            count,
+            world.tick
+        ),
+        world.overlay.graph(),
+        profile(world, true),
+        profile(world, false),
+        entity,
+        components,
+        "F3: hide, [ ]: inspect previous/next".to_string(),
+    ];
+    let columns = terminal::size().map_or(80, |x| x.0) as usize;
+    for (row, text) in (line..).zip(lines) {
+        let text: String = text.chars().take(columns).collect();
+        world.ui.debug_draw(row, &text)?;
+    }
+    world.ui.stdout.flush()
+}
+
+// Milliseconds per frame in each part of the engine, or in the updates of
+// each entity type with how many there were
+fn profile(world: &World, engine: bool) -> String {
+    let parts: Vec<String> = world
+        .profiler
+        .averages(engine)
+        .into_iter()
+        .map(|(name, seconds, calls)| match engine {
+            true => format!("{} {:.2}ms", name, seconds * 1000.0),
+            false => {
+                format!("{} {:.2}ms x{:.0}", name, seconds * 1000.0, calls)
+            }
+        })
+        .collect();
+    parts.join("  ")
+}
+
+// Registered components show their state, the rest only their name
+fn describe_components(world: &World, id: i64) -> String {
+    let Some(components) = world.components.get(&id) else {
+        return String::new();
+    };
+    let mut described: Vec<String> = components
+        .iter()
+        .map(|(key, component)| {
+            let name = world.component_names.get(key).copied().unwrap_or("?");
+            match world.registry.describe_component(key, component.as_ref()) {
+                Some(state) => format!("{} {}", name, state),
+                None => name.to_string(),
+            }
+        })
+        .collect();
+    described.sort();
+    described.join("  ")
+}
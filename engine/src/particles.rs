@@ -0,0 +1,236 @@
+use crate::rng::Rng;
+use crate::{Layer, Map};
+use crossterm::style::Color;
+use std::f64::consts::TAU;
+
+// How particles look and move. Glyphs and colors are spread evenly over a
+// particle's lifetime; two neighbouring Rgb colors are blended.
+#[derive(Clone)]
+pub struct ParticleStyle {
+    pub glyphs: Vec<char>,
+    pub colors: Vec<Color>,
+    pub lifetime: (f64, f64), // min and max seconds
+    pub speed: (f64, f64),    // min and max characters per second
+    pub direction: f64,       // radians, 0 is right and positive y is down
+    pub spread: f64,          // radians around direction, TAU for all round
+    pub gravity: f64,         // added to vertical speed per second
+}
+
+impl ParticleStyle {
+    fn glyph_at(&self, t: f64) -> char {
+        let index = ((t * self.glyphs.len() as f64) as usize)
+            .min(self.glyphs.len().saturating_sub(1));
+        self.glyphs.get(index).copied().unwrap_or(' ')
+    }
+
+    fn color_at(&self, t: f64) -> Color {
+        if self.colors.len() < 2 {
+            return self.colors.first().copied().unwrap_or(Color::White);
+        }
+        let scaled = t.clamp(0.0, 1.0) * (self.colors.len() - 1) as f64;
+        let index = (scaled as usize).min(self.colors.len() - 2);
+        let blend = scaled - index as f64;
+        match (self.colors[index], self.colors[index + 1]) {
+            (
+                Color::Rgb { r, g, b },
+                Color::Rgb {
+                    r: r2,
+                    g: g2,
+                    b: b2,
+                },
+            ) => {
+                let mix = |a: u8, b: u8| {
+                    (a as f64 + (b as f64 - a as f64) * blend).round() as u8
+                };
+                Color::Rgb {
+                    r: mix(r, r2),
+                    g: mix(g, g2),
+                    b: mix(b, b2),
+                }
+            }
+            (from, to) => {
+                if blend < 0.5 {
+                    from
+                } else {
+                    to
+                }
+            }
+        }
+    }
+}
+
+struct Particle {
+    position: (f64, f64),
+    velocity: (f64, f64),
+    age: f64,
+    lifetime: f64,
+    style: usize, // index into Particles::styles
+}
+
+pub struct Emitter {
+    pub position: (f64, f64),
+    pub style: ParticleStyle,
+    pub rate: f64,      // particles per second
+    pub remaining: f64, // seconds left, infinite emitters run until stopped
+    accumulator: f64,
+    id: u64,
+}
+
+impl Emitter {
+    pub fn new(
+        position: (u16, u16),
+        style: ParticleStyle,
+        rate: f64,
+        duration: f64,
+    ) -> Self {
+        Emitter {
+            position: (position.0 as f64, position.1 as f64),
+            style,
+            rate,
+            remaining: duration,
+            accumulator: 0.0,
+            id: 0,
+        }
+    }
+}
+
+// Lives in the World and is painted onto the Effects layer after entities
+// update, so particles never show up in collision queries
+pub struct Particles {
+    particles: Vec<Particle>,
+    styles: Vec<ParticleStyle>,
+    emitters: Vec<Emitter>,
+    next_emitter: u64,
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Particles {
+            particles: vec![],
+            styles: vec![],
+            emitters: vec![],
+            next_emitter: 0,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn burst(
+        &mut self,
+        rng: &mut Rng,
+        position: (u16, u16),
+        count: usize,
+        style: &ParticleStyle,
+    ) {
+        let style_index = self.style_index(style);
+        let origin = (position.0 as f64, position.1 as f64);
+        for _ in 0..count {
+            self.spawn(rng, origin, style, style_index);
+        }
+    }
+
+    // Returns a handle for move_emitter and stop_emitter
+    pub fn add_emitter(&mut self, mut emitter: Emitter) -> u64 {
+        emitter.id = self.next_emitter;
+        self.next_emitter += 1;
+        let id = emitter.id;
+        self.emitters.push(emitter);
+        id
+    }
+
+    pub fn move_emitter(&mut self, emitter: u64, position: (u16, u16)) {
+        if let Some(x) = self.emitters.iter_mut().find(|x| x.id == emitter) {
+            x.position = (position.0 as f64, position.1 as f64);
+        }
+    }
+
+    pub fn stop_emitter(&mut self, emitter: u64) {
+        self.emitters.retain(|x| x.id != emitter);
+    }
+
+    pub fn update(&mut self, delta: f64, rng: &mut Rng) {
+        let mut emitters = std::mem::take(&mut self.emitters);
+        for emitter in emitters.iter_mut() {
+            emitter.accumulator += emitter.rate * delta;
+            emitter.remaining -= delta;
+            let style_index = self.style_index(&emitter.style);
+            while emitter.accumulator >= 1.0 {
+                emitter.accumulator -= 1.0;
+                self.spawn(rng, emitter.position, &emitter.style, style_index);
+            }
+        }
+        emitters.retain(|x| x.remaining > 0.0);
+        self.emitters = emitters;
+
+        for particle in self.particles.iter_mut() {
+            particle.age += delta;
+            particle.velocity.1 += self.styles[particle.style].gravity * delta;
+            particle.position.0 += particle.velocity.0 * delta;
+            particle.position.1 += particle.velocity.1 * delta;
+        }
+        self.particles.retain(|x| x.age < x.lifetime);
+        if self.particles.is_empty() && self.emitters.is_empty() {
+            self.styles.clear();
+        }
+    }
+
+    pub fn draw(&self, map: &mut Map) {
+        for particle in self.particles.iter() {
+            let (x, y) =
+                (particle.position.0.round(), particle.position.1.round());
+            if x < 0.0 || y < 0.0 {
+                continue;
+            }
+            let style = &self.styles[particle.style];
+            let t = particle.age / particle.lifetime;
+            map.paint(
+                (x as u16, y as u16),
+                style.glyph_at(t),
+                style.color_at(t),
+                Layer::Effects,
+            );
+        }
+    }
+
+    // Only what matters after spawning is shared between particles, so
+    // styles that differ in lifetime or speed still reuse one slot
+    fn style_index(&mut self, style: &ParticleStyle) -> usize {
+        let found = self.styles.iter().position(|x| {
+            x.glyphs == style.glyphs
+                && x.colors == style.colors
+                && x.gravity == style.gravity
+        });
+        match found {
+            Some(index) => index,
+            None => {
+                self.styles.push(style.clone());
+                self.styles.len() - 1
+            }
+        }
+    }
+
+    fn spawn(
+        &mut self,
+        rng: &mut Rng,
+        origin: (f64, f64),
+        style: &ParticleStyle,
+        style_index: usize,
+    ) {
+        let between = |rng: &mut Rng, range: (f64, f64)| {
+            range.0 + (range.1 - range.0) * rng.next_f64()
+        };
+        let lifetime = between(rng, style.lifetime);
+        let speed = between(rng, style.speed);
+        let angle =
+            style.direction + (rng.next_f64() - 0.5) * style.spread.min(TAU);
+        self.particles.push(Particle {
+            position: origin,
+            velocity: (angle.cos() * speed, angle.sin() * speed),
+            age: 0.0,
+            lifetime,
+            style: style_index,
+        });
+    }
+}
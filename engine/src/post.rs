@@ -0,0 +1,167 @@
+use crate::rng::Rng;
+use crate::style::{to_rgb, Style};
+use crossterm::style::Color;
+
+#[derive(Clone, Copy)]
+struct Timed {
+    duration: f64,
+    remaining: f64,
+}
+
+impl Timed {
+    fn new(duration: f64) -> Self {
+        Timed {
+            duration,
+            remaining: duration,
+        }
+    }
+
+    // 1 when it starts, 0 when it is over
+    fn left(&self) -> f64 {
+        if self.duration <= 0.0 {
+            0.0
+        } else {
+            (self.remaining / self.duration).clamp(0.0, 1.0)
+        }
+    }
+}
+
+// Applied to the map as it is drawn, so nothing here changes what entities
+// see or collide with. Turning `enabled` off drops every effect, for
+// players who are bothered by shaking or flashing.
+pub struct PostEffects {
+    pub enabled: bool,
+    pub intensity: f64, // scales shake distance and flash strength
+    pub tint: Option<(Color, f64)>, // color and how much of it, 0 to 1
+    shake: Option<(u16, Timed)>,
+    flash: Option<(Color, Timed)>,
+    darkness: f64, // 1 is black
+    fade_speed: f64,
+    offset: (i32, i32),
+    recolored: bool, // whether colors were changed on the last draw
+}
+
+impl PostEffects {
+    pub fn new() -> Self {
+        PostEffects {
+            enabled: true,
+            intensity: 1.0,
+            tint: None,
+            shake: None,
+            flash: None,
+            darkness: 0.0,
+            fade_speed: 0.0,
+            offset: (0, 0),
+            recolored: false,
+        }
+    }
+
+    // Strength is how many tiles the picture may jump at first, it calms
+    // down over the duration
+    pub fn shake(&mut self, strength: u16, duration: f64) {
+        self.shake = Some((strength, Timed::new(duration)));
+    }
+
+    pub fn flash(&mut self, color: Color, duration: f64) {
+        self.flash = Some((color, Timed::new(duration)));
+    }
+
+    // Starts from black
+    pub fn fade_in(&mut self, duration: f64) {
+        self.darkness = 1.0;
+        self.fade_speed = -1.0 / duration.max(f64::EPSILON);
+    }
+
+    // Stays black until the next fade_in
+    pub fn fade_out(&mut self, duration: f64) {
+        self.fade_speed = 1.0 / duration.max(f64::EPSILON);
+    }
+
+    pub fn update(&mut self, delta: f64, rng: &mut Rng) {
+        self.darkness =
+            (self.darkness + self.fade_speed * delta).clamp(0.0, 1.0);
+        if let Some((_, timed)) = self.flash.as_mut() {
+            timed.remaining -= delta;
+        }
+        self.flash.take_if(|(_, x)| x.remaining <= 0.0);
+
+        self.offset = (0, 0);
+        if let Some((strength, timed)) = self.shake.as_mut() {
+            timed.remaining -= delta;
+            let reach = (*strength as f64 * self.intensity * timed.left())
+                .ceil() as i32;
+            if reach > 0 {
+                let mut jump =
+                    || rng.index(2 * reach as usize + 1) as i32 - reach;
+                self.offset = (jump(), jump());
+            }
+        }
+        self.shake.take_if(|(_, x)| x.remaining <= 0.0);
+    }
+
+    // How far the picture is moved this tick
+    pub fn offset(&self) -> (i32, i32) {
+        if self.enabled {
+            self.offset
+        } else {
+            (0, 0)
+        }
+    }
+
+    fn recoloring(&self) -> bool {
+        self.enabled
+            && (self.flash.is_some()
+                || self.darkness > 0.0
+                || self.tint.is_some_and(|(_, x)| x > 0.0))
+    }
+
+    // Every tile changes color while a color effect runs, and once more to
+    // go back to normal after it ends
+    pub(crate) fn needs_redraw(&mut self) -> bool {
+        let recoloring = self.recoloring();
+        let redraw = recoloring || self.recolored;
+        self.recolored = recoloring;
+        redraw
+    }
+
+    pub(crate) fn apply(&self, mut style: Style) -> Style {
+        if !self.recoloring() {
+            return style;
+        }
+        // terminal defaults have no known color, assume light on dark
+        let mut colors = [
+            to_rgb(style.foreground).unwrap_or((192, 192, 192)),
+            to_rgb(style.background).unwrap_or((0, 0, 0)),
+        ];
+        let mut blend = |toward: (u8, u8, u8), amount: f64| {
+            for color in colors.iter_mut() {
+                *color = mix(*color, toward, amount.clamp(0.0, 1.0));
+            }
+        };
+        if let Some((color, amount)) = self.tint {
+            blend(to_rgb(color).unwrap_or((0, 0, 0)), amount);
+        }
+        if let Some((color, timed)) = self.flash {
+            blend(
+                to_rgb(color).unwrap_or((255, 255, 255)),
+                timed.left() * self.intensity,
+            );
+        }
+        blend((0, 0, 0), self.darkness);
+        let rgb = |(r, g, b): (u8, u8, u8)| Color::Rgb { r, g, b };
+        style.foreground = rgb(colors[0]);
+        style.background = rgb(colors[1]);
+        style
+    }
+}
+
+fn mix(from: (u8, u8, u8), to: (u8, u8, u8), amount: f64) -> (u8, u8, u8) {
+    let channel = |a: u8, b: u8| {
+        (a as f64 + (b as f64 - a as f64) * amount).round() as u8
+    };
+    (
+        channel(from.0, to.0),
+        channel(from.1, to.1),
+        channel(from.2, to.2),
+    )
+}
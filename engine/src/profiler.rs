@@ -0,0 +1,114 @@
+use serde::Serialize;
+use std::{collections::HashMap, fs, io, path::PathBuf, time::Instant};
+
+const SMOOTHING: f64 = 0.1; // weight of the newest frame in the averages
+const MAX_TRACE_EVENTS: usize = 1_000_000;
+
+// Engine work, as opposed to the entity types the rest are named after
+pub const ENGINE: [&str; 5] = ["input", "tick", "particles", "draw", "flush"];
+
+// One span in Chrome's trace event format, chrome://tracing and Perfetto
+// open files of these
+#[derive(Serialize)]
+struct Event {
+    name: &'static str,
+    cat: &'static str,
+    ph: &'static str,
+    ts: f64, // microseconds
+    dur: f64,
+    pid: u32,
+    tid: u32,
+}
+
+// Time spent in the parts of the engine and in the updates of each entity
+// type, averaged over recent frames for the overlay and optionally kept as
+// a trace of every span
+pub struct Profiler {
+    start: Instant,
+    frame: HashMap<&'static str, (f64, u32)>, // seconds and calls so far
+    averages: HashMap<&'static str, (f64, f64)>, // per frame
+    trace: Option<(Vec<Event>, PathBuf)>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler {
+            start: Instant::now(),
+            frame: HashMap::new(),
+            averages: HashMap::new(),
+            trace: None,
+        }
+    }
+
+    // Keeps every span until the game quits, then writes them to `path`
+    pub fn trace(&mut self, path: impl Into<PathBuf>) {
+        self.trace = Some((vec![], path.into()));
+    }
+
+    pub(crate) fn span(&mut self, name: &'static str, since: Instant) {
+        let seconds = since.elapsed().as_secs_f64();
+        let entry = self.frame.entry(name).or_default();
+        entry.0 += seconds;
+        entry.1 += 1;
+        let Some((events, _)) = self.trace.as_mut() else {
+            return;
+        };
+        if events.len() == MAX_TRACE_EVENTS {
+            return;
+        }
+        let cat = if ENGINE.contains(&name) {
+            "engine"
+        } else {
+            "entity"
+        };
+        events.push(Event {
+            name,
+            cat,
+            ph: "X",
+            ts: (since - self.start).as_secs_f64() * 1e6,
+            dur: seconds * 1e6,
+            pid: 1,
+            tid: 1,
+        });
+        if events.len() == MAX_TRACE_EVENTS {
+            log::warn!("trace is full, later spans are left out");
+        }
+    }
+
+    pub(crate) fn end_frame(&mut self) {
+        for (name, average) in self.averages.iter_mut() {
+            let (seconds, calls) = self.frame.remove(name).unwrap_or_default();
+            average.0 += (seconds - average.0) * SMOOTHING;
+            average.1 += (calls as f64 - average.1) * SMOOTHING;
+        }
+        for (name, (seconds, calls)) in self.frame.drain() {
+            self.averages.insert(name, (seconds, calls as f64));
+        }
+        // types that are all gone fade out
+        self.averages.retain(|_, x| x.1 > 0.01);
+    }
+
+    // Average seconds and calls per frame, slowest first
+    pub fn averages(&self, engine: bool) -> Vec<(&'static str, f64, f64)> {
+        let mut averages: Vec<(&'static str, f64, f64)> = self
+            .averages
+            .iter()
+            .filter(|(name, _)| ENGINE.contains(name) == engine)
+            .map(|(name, (seconds, calls))| (*name, *seconds, *calls))
+            .collect();
+        averages.sort_by(|x, y| y.1.total_cmp(&x.1).then(x.0.cmp(y.0)));
+        averages
+    }
+
+    pub(crate) fn save_trace(&self) -> io::Result<()> {
+        let Some((events, path)) = &self.trace else {
+            return Ok(());
+        };
+        log::info!("saving {} spans to {}", events.len(), path.display());
+        let trace = serde_json::json!({
+            "traceEvents": events,
+            "displayTimeUnit": "ms",
+        });
+        fs::write(path, trace.to_string())
+    }
+}
@@ -0,0 +1,271 @@
+use crossterm::event::KeyCode;
+use std::{error::Error, fmt, fs, io, path::Path};
+
+const VERSION: u32 = 1;
+const FAST_FORWARD: u64 = 4; // ticks per frame
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(x) => write!(f, "could not read replay: {}", x),
+            ReplayError::Format(x) => write!(f, "not a replay file: {}", x),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(error: io::Error) -> Self {
+        ReplayError::Io(error)
+    }
+}
+
+// Everything needed to run a game again tick for tick: the seed, the fixed
+// timestep, whatever configuration the game wants back and the input of
+// every tick that had some. A line based text file, so it can be diffed and
+// attached to bug reports.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub timestep: f64,
+    pub args: Vec<String>,
+    ticks: u64,
+    inputs: Vec<(u64, KeyCode)>, // in tick order
+}
+
+impl Replay {
+    pub fn new(seed: u64, timestep: f64, args: Vec<String>) -> Self {
+        Replay {
+            seed,
+            timestep,
+            args,
+            ticks: 0,
+            inputs: vec![],
+        }
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    // Called once per tick, in order
+    pub fn record(&mut self, tick: u64, input: Option<KeyCode>) {
+        if let Some(key) = input.filter(|x| key_name(*x).is_some()) {
+            self.inputs.push((tick, key));
+        }
+        self.ticks = self.ticks.max(tick + 1);
+    }
+
+    pub fn input(&self, tick: u64) -> Option<KeyCode> {
+        self.inputs
+            .binary_search_by_key(&tick, |(x, _)| *x)
+            .ok()
+            .map(|i| self.inputs[i].1)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Replay::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_text())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("replay {}\n", VERSION);
+        text += &format!("seed {}\n", self.seed);
+        // {:?} prints the shortest text that parses back to the same f64
+        text += &format!("timestep {:?}\n", self.timestep);
+        text += &format!("ticks {}\n", self.ticks);
+        for arg in self.args.iter() {
+            text += &format!("arg {}\n", arg);
+        }
+        for (tick, key) in self.inputs.iter() {
+            if let Some(name) = key_name(*key) {
+                text += &format!("input {} {}\n", tick, name);
+            }
+        }
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ReplayError> {
+        let error = |x: &str| ReplayError::Format(x.to_string());
+        let mut lines = text.lines();
+        match lines.next() {
+            Some(x) if x == format!("replay {}", VERSION) => {}
+            Some(x) if x.starts_with("replay ") => {
+                return Err(error("unsupported version"))
+            }
+            _ => return Err(error("missing replay header")),
+        }
+        let mut seed = None;
+        let mut timestep = None;
+        let mut replay = Replay::new(0, 0.0, vec![]);
+        for (i, line) in lines.enumerate() {
+            let bad =
+                || ReplayError::Format(format!("line {}: {}", i + 2, line));
+            let (field, value) = line.split_once(' ').ok_or_else(bad)?;
+            match field {
+                "seed" => seed = Some(value.parse().map_err(|_| bad())?),
+                "timestep" => {
+                    timestep = value.parse().ok().filter(|x: &f64| *x > 0.0);
+                    timestep.ok_or_else(bad)?;
+                }
+                "ticks" => replay.ticks = value.parse().map_err(|_| bad())?,
+                "arg" => replay.args.push(value.to_string()),
+                "input" => {
+                    let (tick, name) = value.split_once(' ').ok_or_else(bad)?;
+                    let tick: u64 = tick.parse().map_err(|_| bad())?;
+                    let key = parse_key(name).ok_or_else(bad)?;
+                    if replay.inputs.last().is_some_and(|x| x.0 >= tick) {
+                        return Err(bad());
+                    }
+                    replay.inputs.push((tick, key));
+                }
+                _ => return Err(bad()),
+            }
+        }
+        let (Some(seed), Some(timestep)) = (seed, timestep) else {
+            return Err(error("missing seed or timestep"));
+        };
+        replay.seed = seed;
+        replay.timestep = timestep;
+        if let Some((tick, _)) = replay.inputs.last() {
+            replay.ticks = replay.ticks.max(tick + 1);
+        }
+        Ok(replay)
+    }
+}
+
+const NAMED_KEYS: [(KeyCode, &str); 15] = [
+    (KeyCode::Left, "left"),
+    (KeyCode::Right, "right"),
+    (KeyCode::Up, "up"),
+    (KeyCode::Down, "down"),
+    (KeyCode::Enter, "enter"),
+    (KeyCode::Esc, "esc"),
+    (KeyCode::Backspace, "backspace"),
+    (KeyCode::Tab, "tab"),
+    (KeyCode::BackTab, "backtab"),
+    (KeyCode::Delete, "delete"),
+    (KeyCode::Insert, "insert"),
+    (KeyCode::Home, "home"),
+    (KeyCode::End, "end"),
+    (KeyCode::PageUp, "pageup"),
+    (KeyCode::PageDown, "pagedown"),
+];
+
+// Keys without a name are left out of replays, nothing in a game reacts to
+// media or modifier keys on their own
+fn key_name(key: KeyCode) -> Option<String> {
+    match key {
+        KeyCode::Char(x) => Some(format!("char {}", x as u32)),
+        KeyCode::F(x) => Some(format!("f{}", x)),
+        key => NAMED_KEYS
+            .iter()
+            .find(|(x, _)| *x == key)
+            .map(|(_, name)| name.to_string()),
+    }
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    if let Some(code) = name.strip_prefix("char ") {
+        return char::from_u32(code.parse().ok()?).map(KeyCode::Char);
+    }
+    if let Some(number) = name.strip_prefix('f').and_then(|x| x.parse().ok()) {
+        return Some(KeyCode::F(number));
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|(_, x)| *x == name)
+        .map(|(key, _)| *key)
+}
+
+// A replay being played back. Keys pressed meanwhile steer the playback
+// instead of reaching the game: space pauses, f toggles fast-forward.
+pub(crate) struct Playback {
+    pub replay: Replay,
+    pub paused: bool,
+    pub fast_forward: bool,
+}
+
+impl Playback {
+    // How many ticks to run this frame
+    pub fn ticks(&mut self, key: Option<KeyCode>, tick: u64) -> u64 {
+        match key {
+            Some(KeyCode::Char(' ')) => self.paused = !self.paused,
+            Some(KeyCode::Char('f')) => self.fast_forward = !self.fast_forward,
+            _ => {}
+        }
+        let left = self.replay.ticks().saturating_sub(tick);
+        match (self.paused, self.fast_forward) {
+            (true, _) => 0,
+            (false, true) => left.min(FAST_FORWARD),
+            (false, false) => left.min(1),
+        }
+    }
+
+    pub fn status(&self, tick: u64) -> String {
+        let state = if tick >= self.replay.ticks() {
+            "finished"
+        } else if self.paused {
+            "paused"
+        } else if self.fast_forward {
+            "fast-forward"
+        } else {
+            "playing"
+        };
+        format!(
+            "REPLAY {}/{} {} - space: pause, f: fast-forward, q: quit",
+            tick,
+            self.replay.ticks(),
+            state
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_parses_back_to_the_same_replay() {
+        let args = vec!["--size".to_string(), "25x15".to_string()];
+        let mut replay = Replay::new(42, 0.1 + 0.2, args);
+        replay.record(0, Some(KeyCode::Left));
+        replay.record(1, None);
+        replay.record(2, Some(KeyCode::Char(' ')));
+        replay.record(3, Some(KeyCode::F(5)));
+        replay.record(4, Some(KeyCode::Null)); // has no name, left out
+        replay.record(9, None);
+        let parsed = Replay::parse(&replay.to_text()).expect("replay parses");
+        assert_eq!(parsed, replay);
+        assert_eq!(parsed.ticks(), 10);
+        assert_eq!(parsed.input(2), Some(KeyCode::Char(' ')));
+        assert_eq!(parsed.input(4), None);
+    }
+
+    #[test]
+    fn bad_replays_are_format_errors() {
+        let texts = [
+            "",
+            "replay 0\nseed 1\ntimestep 0.04\n",
+            "replay 1\ntimestep 0.04\n",
+            "replay 1\nseed 1\ntimestep 0\n",
+            "replay 1\nseed 1\ntimestep 0.04\ninput 3 left\ninput 2 up\n",
+            "replay 1\nseed 1\ntimestep 0.04\ninput 3 hyper\n",
+            "replay 1\nseed 1\ntimestep 0.04\nspeed 9\n",
+        ];
+        for text in texts {
+            let error = Replay::parse(text).err();
+            assert!(matches!(error, Some(ReplayError::Format(_))), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crossterm::event::KeyCode;
+use serde_json::Value;
+use std::collections::VecDeque;
+
+// The world as it was before one tick, with the input that tick got
+struct Frame {
+    snapshot: String,
+    input: Option<KeyCode>,
+    delta: f64,
+}
+
+pub(crate) enum Step {
+    Nothing,
+    Show(usize),
+    Simulate, // past the newest frame, run a tick to make a new one
+}
+
+// Keeps the last `capacity` ticks so they can be stepped through while the
+// game is paused. A frame is shown by restoring the snapshot before it and
+// running its tick again with the same input, which also draws it.
+pub(crate) struct Rewind {
+    capacity: usize,
+    frames: VecDeque<Frame>,
+    pub cursor: Option<usize>, // the frame on screen while paused
+    pub inspected: Option<usize>, // index into the frame's entities
+}
+
+impl Rewind {
+    pub fn new(capacity: usize) -> Self {
+        Rewind {
+            capacity: capacity.max(1),
+            frames: VecDeque::new(),
+            cursor: None,
+            inspected: None,
+        }
+    }
+
+    pub fn paused(&self) -> bool {
+        self.cursor.is_some()
+    }
+
+    pub fn push(
+        &mut self,
+        snapshot: String,
+        input: Option<KeyCode>,
+        delta: f64,
+    ) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(Frame {
+            snapshot,
+            input,
+            delta,
+        });
+    }
+
+    pub fn frame(&self, index: usize) -> Option<(&str, Option<KeyCode>, f64)> {
+        self.frames
+            .get(index)
+            .map(|x| (x.snapshot.as_str(), x.input, x.delta))
+    }
+
+    pub fn pause(&mut self) {
+        self.cursor = self.frames.len().checked_sub(1);
+    }
+
+    // Whatever came after the frame on screen did not happen now
+    pub fn resume(&mut self) {
+        if let Some(cursor) = self.cursor.take() {
+            self.frames.truncate(cursor + 1);
+        }
+        self.inspected = None;
+    }
+
+    pub fn back(&mut self) -> Step {
+        match self.cursor {
+            Some(cursor) if cursor > 0 => {
+                self.cursor = Some(cursor - 1);
+                Step::Show(cursor - 1)
+            }
+            _ => Step::Nothing,
+        }
+    }
+
+    pub fn forward(&mut self) -> Step {
+        match self.cursor {
+            Some(cursor) if cursor + 1 < self.frames.len() => {
+                self.cursor = Some(cursor + 1);
+                Step::Show(cursor + 1)
+            }
+            Some(_) => Step::Simulate,
+            None => Step::Nothing,
+        }
+    }
+
+    // Cycles through the entities of the frame on screen, then back to none
+    pub fn inspect_next(&mut self) {
+        let count = self.entities().map_or(0, |x| x.len());
+        self.inspected = match self.inspected {
+            None if count > 0 => Some(0),
+            Some(i) if i + 1 < count => Some(i + 1),
+            _ => None,
+        };
+    }
+
+    fn entities(&self) -> Option<Vec<Value>> {
+        let (snapshot, _, _) = self.frame(self.cursor?)?;
+        let mut snapshot: Value = serde_json::from_str(snapshot).ok()?;
+        match snapshot.get_mut("entities")?.take() {
+            Value::Array(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> String {
+        let behind = self.frames.len() - 1 - self.cursor.unwrap_or(0);
+        format!(
+            "REWIND -{} of {} ticks - left/right: step, i: inspect, p: resume",
+            behind,
+            self.frames.len() - 1
+        )
+    }
+
+    // The inspected entity as it was before the tick on screen, one line
+    // for the entity and one per component
+    pub fn inspection(&self) -> Vec<String> {
+        let Some(entity) = self
+            .inspected
+            .and_then(|i| self.entities()?.into_iter().nth(i))
+        else {
+            return vec![];
+        };
+        let mut lines = vec![format!(
+            "#{} {} {}",
+            entity["id"],
+            entity["type"].as_str().unwrap_or("?"),
+            entity["state"]
+        )];
+        if let Value::Object(components) = &entity["components"] {
+            for (name, value) in components {
+                lines.push(format!("  {}: {}", name, value));
+            }
+        }
+        lines
+    }
+}
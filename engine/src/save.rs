@@ -0,0 +1,318 @@
+use crate::particles::Particles;
+use crate::rng::Rng;
+use crate::{short_type_name, Entity, EntityData, Map, World};
+use crossterm::style::Color;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    any::{Any, TypeId},
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt, io,
+};
+
+const VERSION: u32 = 2;
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(x) => write!(f, "could not access save: {}", x),
+            SaveError::Format(x) => write!(f, "bad save: {}", x),
+        }
+    }
+}
+
+impl Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        SaveError::Format(error.to_string())
+    }
+}
+
+struct Entry<T: ?Sized> {
+    name: &'static str,
+    type_name: &'static str,
+    save: Box<dyn Fn(&T) -> serde_json::Result<Value>>,
+    load: Box<dyn Fn(Value) -> serde_json::Result<Box<T>>>,
+}
+
+// The entity and component types a save can hold, each under a name that
+// stays the same between builds. Types that are not registered are left
+// out of saves.
+#[derive(Default)]
+pub struct Registry {
+    entities: Vec<(TypeId, Entry<dyn Entity>)>,
+    components: Vec<(String, Entry<dyn Any>)>, // keyed like World's
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    pub fn entity<T>(&mut self, name: &'static str) -> &mut Self
+    where
+        T: Entity + Serialize + DeserializeOwned,
+    {
+        let entry = Entry {
+            name,
+            type_name: short_type_name::<T>(),
+            save: Box::new(|x: &dyn Entity| {
+                let any: &dyn Any = x;
+                serde_json::to_value(any.downcast_ref::<T>())
+            }),
+            load: Box::new(|x| {
+                Ok(Box::new(serde_json::from_value::<T>(x)?) as Box<dyn Entity>)
+            }),
+        };
+        self.entities.push((TypeId::of::<T>(), entry));
+        self
+    }
+
+    // For entities holding things that are not worth saving or can't be,
+    // like fonts. `save` picks out what to keep and `load` builds the
+    // entity back up from it.
+    pub fn entity_as<T, S>(
+        &mut self,
+        name: &'static str,
+        save: impl Fn(&T) -> S + 'static,
+        load: impl Fn(S) -> T + 'static,
+    ) -> &mut Self
+    where
+        T: Entity,
+        S: Serialize + DeserializeOwned,
+    {
+        let entry = Entry {
+            name,
+            type_name: short_type_name::<T>(),
+            save: Box::new(move |x: &dyn Entity| {
+                let any: &dyn Any = x;
+                serde_json::to_value(any.downcast_ref::<T>().map(&save))
+            }),
+            load: Box::new(move |x| {
+                let state = serde_json::from_value::<S>(x)?;
+                Ok(Box::new(load(state)) as Box<dyn Entity>)
+            }),
+        };
+        self.entities.push((TypeId::of::<T>(), entry));
+        self
+    }
+
+    pub fn component<T>(&mut self, name: &'static str) -> &mut Self
+    where
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        let entry = Entry {
+            name,
+            type_name: short_type_name::<T>(),
+            save: Box::new(|x: &dyn Any| {
+                serde_json::to_value(x.downcast_ref::<T>())
+            }),
+            load: Box::new(|x| {
+                Ok(Box::new(serde_json::from_value::<T>(x)?) as Box<dyn Any>)
+            }),
+        };
+        let key = format!("{:?}", TypeId::of::<T>());
+        self.components.push((key, entry));
+        self
+    }
+
+    fn save_entity(
+        &self,
+        entity: &dyn Entity,
+    ) -> serde_json::Result<Option<(String, Value)>> {
+        let any: &dyn Any = entity;
+        match self.entities.iter().find(|(x, _)| *x == any.type_id()) {
+            Some((_, entry)) => {
+                Ok(Some((entry.name.to_string(), (entry.save)(entity)?)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn load_entity(
+        &self,
+        name: &str,
+        state: Value,
+    ) -> Result<(Box<dyn Entity>, &'static str), SaveError> {
+        let (_, entry) = self
+            .entities
+            .iter()
+            .find(|(_, x)| x.name == name)
+            .ok_or(SaveError::Format(format!("unknown entity {}", name)))?;
+        Ok(((entry.load)(state)?, entry.type_name))
+    }
+
+    fn save_component(
+        &self,
+        key: &str,
+        component: &dyn Any,
+    ) -> serde_json::Result<Option<(String, Value)>> {
+        match self.components.iter().find(|(x, _)| x == key) {
+            Some((_, entry)) => {
+                Ok(Some((entry.name.to_string(), (entry.save)(component)?)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn describe_component(
+        &self,
+        key: &str,
+        component: &dyn Any,
+    ) -> Option<String> {
+        let (_, state) = self.save_component(key, component).ok()??;
+        Some(state.to_string())
+    }
+
+    fn load_component(
+        &self,
+        name: &str,
+        state: Value,
+    ) -> Result<(String, Box<dyn Any>, &'static str), SaveError> {
+        let (key, entry) =
+            self.components.iter().find(|(_, x)| x.name == name).ok_or(
+                SaveError::Format(format!("unknown component {}", name)),
+            )?;
+        Ok((key.clone(), (entry.load)(state)?, entry.type_name))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    tick: u64,
+    next_id: i64,
+    // positions only make sense on a map of the same size
+    map_size: (u16, u16),
+    rng: Rng,
+    tint: Option<(Color, f64)>,
+    entities: Vec<SavedEntity>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedEntity {
+    id: i64,
+    #[serde(rename = "type")]
+    name: String,
+    started: bool,
+    state: Value,
+    components: BTreeMap<String, Value>,
+}
+
+// Compact for the rewind buffer, which keeps a lot of these, and pretty for
+// files people may want to read
+pub(crate) fn snapshot(
+    world: &World,
+    pretty: bool,
+) -> Result<String, SaveError> {
+    let mut entities = vec![];
+    for data in world.entities.iter() {
+        if world.removal_queue.contains(&data.id) {
+            continue;
+        }
+        let Some((name, state)) = world.registry.save_entity(&*data.entity)?
+        else {
+            continue;
+        };
+        let mut components = BTreeMap::new();
+        for (key, component) in
+            world.components.get(&data.id).into_iter().flatten()
+        {
+            if let Some((name, value)) =
+                world.registry.save_component(key, component.as_ref())?
+            {
+                components.insert(name, value);
+            }
+        }
+        entities.push(SavedEntity {
+            id: data.id,
+            name,
+            started: data.started,
+            state,
+            components,
+        });
+    }
+    let snapshot = Snapshot {
+        version: VERSION,
+        tick: world.tick,
+        next_id: world.next_id,
+        map_size: world.map.size(),
+        rng: world.rng.clone(),
+        tint: world.post.tint,
+        entities,
+    };
+    if pretty {
+        Ok(serde_json::to_string_pretty(&snapshot)?)
+    } else {
+        Ok(serde_json::to_string(&snapshot)?)
+    }
+}
+
+// Everything is read before the world is touched, so a bad save leaves the
+// game running as it was
+pub(crate) fn restore(world: &mut World, text: &str) -> Result<(), SaveError> {
+    let snapshot: Snapshot = serde_json::from_str(text)?;
+    if snapshot.version != VERSION {
+        return Err(SaveError::Format(format!(
+            "unsupported version {}",
+            snapshot.version
+        )));
+    }
+    if snapshot.map_size != world.map.size() {
+        let ((w, h), (width, height)) = (snapshot.map_size, world.map.size());
+        return Err(SaveError::Format(format!(
+            "saved on a {}x{} map, this one is {}x{}",
+            w, h, width, height
+        )));
+    }
+    let mut entities = vec![];
+    let mut components = HashMap::new();
+    let mut names = HashMap::new();
+    for saved in snapshot.entities {
+        let (entity, type_name) =
+            world.registry.load_entity(&saved.name, saved.state)?;
+        let mut loaded = HashMap::new();
+        for (name, state) in saved.components {
+            let (key, component, type_name) =
+                world.registry.load_component(&name, state)?;
+            names.insert(key.clone(), type_name);
+            loaded.insert(key, component);
+        }
+        entities.push(EntityData {
+            entity,
+            id: saved.id,
+            type_name,
+            started: saved.started,
+        });
+        components.insert(saved.id, loaded);
+    }
+    world.entities = entities;
+    world.components = components;
+    world.component_names.extend(names);
+    world.removal_queue.clear();
+    world.next_id = snapshot.next_id;
+    world.tick = snapshot.tick;
+    world.rng = snapshot.rng;
+    world.post.tint = snapshot.tint;
+    world.particles = Particles::new();
+    // nothing on the map is where the restored entities are
+    world.map = Map::new(world.map.width, world.map.height);
+    for viewport in world.viewports.iter_mut() {
+        viewport.redraw();
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use crate::style::Style;
+use crossterm::style::Color;
+
+// A block of characters drawn as one entity. Spaces are transparent: they
+// are neither drawn nor collided with.
+#[derive(Clone)]
+pub struct Sprite {
+    width: u16,
+    height: u16,
+    anchor: (u16, u16),
+    cells: Vec<Option<(char, Style)>>, // row major
+}
+
+impl Sprite {
+    pub fn new(rows: &[&str], style: impl Into<Style>) -> Self {
+        Sprite::with_styles(rows, &[], &[], style)
+    }
+
+    pub fn with_colors(
+        rows: &[&str],
+        colors: &[&str],
+        palette: &[(char, Color)],
+        color: Color,
+    ) -> Self {
+        let palette: Vec<(char, Style)> = palette
+            .iter()
+            .map(|(x, color)| (*x, (*color).into()))
+            .collect();
+        Sprite::with_styles(rows, colors, &palette, color)
+    }
+
+    // `keys` mirrors `rows` with a key character per cell that is looked
+    // up in `palette`; missing or unknown keys fall back to `style`
+    pub fn with_styles(
+        rows: &[&str],
+        keys: &[&str],
+        palette: &[(char, Style)],
+        style: impl Into<Style>,
+    ) -> Self {
+        let style = style.into();
+        let width = rows.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for (r, row) in rows.iter().enumerate() {
+            let row_keys: Vec<char> =
+                keys.get(r).map_or(vec![], |x| x.chars().collect());
+            let mut glyphs = row.chars();
+            for c in 0..width {
+                let cell = match glyphs.next() {
+                    Some(' ') | None => None,
+                    Some(glyph) => {
+                        let cell_style = row_keys
+                            .get(c)
+                            .and_then(|key| {
+                                palette.iter().find(|(x, _)| x == key)
+                            })
+                            .map_or(style, |(_, x)| *x);
+                        Some((glyph, cell_style))
+                    }
+                };
+                cells.push(cell);
+            }
+        }
+        Sprite {
+            width: width as u16,
+            height: rows.len() as u16,
+            anchor: (0, 0),
+            cells,
+        }
+    }
+
+    // The cell that sits on the position the sprite is drawn at,
+    // top left by default
+    pub fn anchored(mut self, anchor: (u16, u16)) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn anchor(&self) -> (u16, u16) {
+        self.anchor
+    }
+
+    // Solid cells as offsets from the top left corner
+    pub fn cells(&self) -> Vec<((u16, u16), char, Style)> {
+        let mut cells = vec![];
+        for (i, cell) in self.cells.iter().enumerate() {
+            if let Some((glyph, style)) = cell {
+                let offset = (
+                    i as u16 % self.width.max(1),
+                    i as u16 / self.width.max(1),
+                );
+                cells.push((offset, *glyph, *style));
+            }
+        }
+        cells
+    }
+
+    // Solid cells in map coordinates when drawn at `position`. Cells that
+    // would land left of or above the origin are dropped.
+    pub fn placed(
+        &self,
+        position: (u16, u16),
+    ) -> Vec<((u16, u16), char, Style)> {
+        self.cells()
+            .into_iter()
+            .filter_map(|(offset, glyph, style)| {
+                let x = (position.0 + offset.0).checked_sub(self.anchor.0)?;
+                let y = (position.1 + offset.1).checked_sub(self.anchor.1)?;
+                Some(((x, y), glyph, style))
+            })
+            .collect()
+    }
+}
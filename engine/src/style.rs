@@ -0,0 +1,248 @@
+use crossterm::style::{Attribute, Color, ContentStyle};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::env;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Attributes {
+    pub bold: bool,
+    pub dim: bool,
+    pub blink: bool,
+    pub reverse: bool,
+}
+
+// Everything a map cell can look like besides its character. Reset means
+// the terminal's own default color.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Style {
+    #[serde(with = "reset_as_none")]
+    pub foreground: Color,
+    #[serde(with = "reset_as_none")]
+    pub background: Color,
+    pub attributes: Attributes,
+}
+
+// Crossterm refuses to serialize Color::Reset, so it is saved as null
+mod reset_as_none {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        color: &Color,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Some(*color)
+            .filter(|x| *x != Color::Reset)
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Color, D::Error> {
+        Ok(Option::<Color>::deserialize(deserializer)?.unwrap_or(Color::Reset))
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            foreground: Color::Reset,
+            background: Color::Reset,
+            attributes: Attributes::default(),
+        }
+    }
+}
+
+impl From<Color> for Style {
+    fn from(foreground: Color) -> Self {
+        Style {
+            foreground,
+            ..Style::default()
+        }
+    }
+}
+
+impl Style {
+    pub fn on(mut self, background: Color) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn bold(mut self) -> Self {
+        self.attributes.bold = true;
+        self
+    }
+
+    pub fn dim(mut self) -> Self {
+        self.attributes.dim = true;
+        self
+    }
+
+    pub fn blink(mut self) -> Self {
+        self.attributes.blink = true;
+        self
+    }
+
+    pub fn reverse(mut self) -> Self {
+        self.attributes.reverse = true;
+        self
+    }
+
+    pub fn to_content_style(self, depth: ColorDepth) -> ContentStyle {
+        let mut content = ContentStyle::new();
+        content.foreground_color = Some(depth.downgrade(self.foreground));
+        content.background_color = Some(depth.downgrade(self.background));
+        let attributes = [
+            (self.attributes.bold, Attribute::Bold),
+            (self.attributes.dim, Attribute::Dim),
+            (self.attributes.blink, Attribute::SlowBlink),
+            (self.attributes.reverse, Attribute::Reverse),
+        ];
+        for (set, attribute) in attributes {
+            if set {
+                content.attributes.set(attribute);
+            }
+        }
+        content
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+// Standard xterm values for the 16 named colors
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn ansi256_to_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => ANSI16[value as usize].1,
+        16..=231 => {
+            let i = value - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let grey = 8 + 10 * (value - 232);
+            (grey, grey, grey)
+        }
+    }
+}
+
+// Accepts names like "dark_red", "reset", ANSI values like "208" and
+// "#rrggbb"
+pub fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix('#') {
+        let channel =
+            |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return match hex.len() {
+            6 => Some(Color::Rgb {
+                r: channel(0)?,
+                g: channel(2)?,
+                b: channel(4)?,
+            }),
+            _ => None,
+        };
+    }
+    if let Ok(value) = text.parse::<u8>() {
+        return Some(Color::AnsiValue(value));
+    }
+    if text.eq_ignore_ascii_case("reset") {
+        return Some(Color::Reset);
+    }
+    Color::try_from(text).ok()
+}
+
+// None for Reset, which depends on the terminal
+pub fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb { r, g, b } => Some((r, g, b)),
+        Color::AnsiValue(value) => Some(ansi256_to_rgb(value)),
+        Color::Reset => None,
+        named => ANSI16.iter().find(|(x, _)| *x == named).map(|(_, x)| *x),
+    }
+}
+
+fn rgb_to_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |x: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|i| (CUBE_LEVELS[*i] as i32 - x as i32).abs())
+            .unwrap() as u8
+    };
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey = 232 + ((average.saturating_sub(8) / 10).min(23) as u8);
+    if distance(ansi256_to_rgb(grey), rgb) < distance(ansi256_to_rgb(cube), rgb)
+    {
+        grey
+    } else {
+        cube
+    }
+}
+
+fn rgb_to_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, x)| distance(*x, rgb))
+        .map(|(x, _)| *x)
+        .unwrap()
+}
+
+impl ColorDepth {
+    // Going by the same environment variables most terminal programs use
+    pub fn detect() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        if colorterm.contains("truecolor") || colorterm.contains("24bit") {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    pub fn downgrade(&self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::TrueColor, _) => color,
+            (ColorDepth::Ansi256, Color::Rgb { r, g, b }) => {
+                Color::AnsiValue(rgb_to_ansi256((r, g, b)))
+            }
+            (ColorDepth::Ansi16, Color::Rgb { r, g, b }) => {
+                rgb_to_ansi16((r, g, b))
+            }
+            (ColorDepth::Ansi16, Color::AnsiValue(value)) => {
+                rgb_to_ansi16(ansi256_to_rgb(value))
+            }
+            _ => color,
+        }
+    }
+}
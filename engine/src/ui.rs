@@ -1,5 +1,5 @@
 use std::{
-    io::{self, Stdout},
+    io::{self, Stdout, Write},
     sync::mpsc::{self, Receiver},
     thread,
 };
@@ -7,13 +7,23 @@ use std::{
 use crossterm::{
     cursor,
     event::{read, Event, KeyCode},
-    style::{self, Color, Stylize},
+    style::{self, Attribute, Color, ContentStyle, StyledContent, Stylize},
     terminal, QueueableCommand,
 };
+
+use crate::charset::Charset;
+use crate::style::{ColorDepth, Style};
+
+pub mod font;
+
 pub struct UI {
     pub stdout: Stdout,
     pub current_input: Option<KeyCode>,
     pub last_input: Option<KeyCode>,
+    pub color_depth: ColorDepth,
+    pub charset: Charset,
+    // set when drawing goes to memory instead of the terminal
+    pub screen: Option<Screen>,
     input_reciever: Receiver<Option<KeyCode>>,
 }
 impl UI {
@@ -27,6 +37,24 @@ impl UI {
             stdout: io::stdout(),
             current_input: None,
             last_input: None,
+            color_depth: ColorDepth::detect(),
+            charset: Charset::detect(),
+            screen: None,
+            input_reciever: rx,
+        }
+    }
+
+    // Draws into `screen` and reads no keys, for tests. Glyphs are kept as
+    // a Unicode terminal would show them.
+    pub fn headless() -> UI {
+        let (_, rx) = mpsc::channel();
+        UI {
+            stdout: io::stdout(),
+            current_input: None,
+            last_input: None,
+            color_depth: ColorDepth::TrueColor,
+            charset: Charset::Unicode,
+            screen: Some(Screen::default()),
             input_reciever: rx,
         }
     }
@@ -35,19 +63,70 @@ impl UI {
         &mut self,
         position: (u16, u16),
         character: char,
+        style: Style,
+    ) -> io::Result<()> {
+        let character = self.charset.fallback(character);
+        if let Some(screen) = self.screen.as_mut() {
+            screen.write(position, &character.to_string());
+            return Ok(());
+        }
+        let content = style.to_content_style(self.color_depth);
+        self.stdout.queue(cursor::MoveTo(position.0, position.1))?;
+        self.print(content, &character.to_string())
+    }
+
+    pub fn text_draw(
+        &mut self,
+        position: (u16, u16),
+        text: &str,
         color: Color,
     ) -> io::Result<()> {
+        let color = self.color_depth.downgrade(color);
+        let text = self.charset.convert(text);
+        if let Some(screen) = self.screen.as_mut() {
+            screen.write(position, &text);
+            return Ok(());
+        }
+        self.stdout.queue(cursor::MoveTo(position.0, position.1))?;
+        self.print(ContentStyle::new().with(color), &text)
+    }
+
+    pub fn clear_line(&mut self, line: u16) -> io::Result<()> {
+        if let Some(screen) = self.screen.as_mut() {
+            screen.clear_line(line);
+            return Ok(());
+        }
         self.stdout
-            .queue(cursor::MoveTo(position.0, position.1))?
-            .queue(style::PrintStyledContent((character).with(color)))?;
+            .queue(cursor::MoveTo(0, line))?
+            .queue(terminal::Clear(terminal::ClearType::CurrentLine))?;
         Ok(())
     }
 
     pub fn debug_draw(&mut self, line: u16, text: &str) -> io::Result<()> {
+        let text = self.charset.convert(text);
+        if let Some(screen) = self.screen.as_mut() {
+            screen.clear_line(line);
+            screen.write((0, line), &text);
+            return Ok(());
+        }
         self.stdout
             .queue(cursor::MoveTo(0, line))?
-            .queue(terminal::Clear(terminal::ClearType::CurrentLine))?
-            .queue(style::PrintStyledContent((text).with(Color::Red)))?;
+            .queue(terminal::Clear(terminal::ClearType::CurrentLine))?;
+        self.print(ContentStyle::new().with(Color::Red), &text)
+    }
+
+    // Crossterm only writes UTF-8, so Latin-1 text goes out as raw bytes
+    // between the style and its reset
+    fn print(&mut self, content: ContentStyle, text: &str) -> io::Result<()> {
+        if self.charset != Charset::Latin1 {
+            self.stdout.queue(style::PrintStyledContent(
+                StyledContent::new(content, text),
+            ))?;
+            return Ok(());
+        }
+        self.stdout.queue(style::SetStyle(content))?;
+        self.stdout.write_all(&self.charset.encode(text))?;
+        self.stdout.queue(style::SetAttribute(Attribute::Reset))?;
         Ok(())
     }
 
@@ -63,6 +142,59 @@ impl UI {
             Err(_) => self.current_input,
         };
     }
+
+    // Every key that arrived since the last call, for when one a frame is
+    // not enough
+    pub fn pending_inputs(&mut self) -> Vec<KeyCode> {
+        let keys: Vec<KeyCode> =
+            self.input_reciever.try_iter().flatten().collect();
+        if let Some(key) = keys.last() {
+            self.last_input = Some(*key);
+        }
+        keys
+    }
+}
+
+// The characters on a terminal that is not there, growing to fit whatever
+// is drawn on it. Colors are left out.
+#[derive(Default)]
+pub struct Screen {
+    rows: Vec<Vec<char>>,
+}
+
+impl Screen {
+    fn write(&mut self, position: (u16, u16), text: &str) {
+        let (column, row) = (position.0 as usize, position.1 as usize);
+        if self.rows.len() <= row {
+            self.rows.resize(row + 1, vec![]);
+        }
+        let line = &mut self.rows[row];
+        for (i, character) in text.chars().enumerate() {
+            if line.len() <= column + i {
+                line.resize(column + i + 1, ' ');
+            }
+            line[column + i] = character;
+        }
+    }
+
+    fn clear_line(&mut self, row: u16) {
+        if let Some(line) = self.rows.get_mut(row as usize) {
+            line.clear();
+        }
+    }
+
+    // One line per row without trailing blanks, the way it would look
+    pub fn text(&self) -> String {
+        let mut lines: Vec<String> = self
+            .rows
+            .iter()
+            .map(|x| x.iter().collect::<String>().trim_end().to_string())
+            .collect();
+        while lines.last().is_some_and(|x| x.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
 }
 
 fn read_inputs() -> Option<KeyCode> {
@@ -0,0 +1,289 @@
+use crate::style::Style;
+use crate::{Layer, Map};
+use std::{collections::HashMap, error::Error, fmt, fs, io, path::Path};
+
+// 3x5 block letters, lowercase is drawn as uppercase
+const BLOCK_GLYPHS: [(char, [&str; 5]); 44] = [
+    ('A', [" # ", "# #", "###", "# #", "# #"]),
+    ('B', ["## ", "# #", "## ", "# #", "## "]),
+    ('C', [" ##", "#  ", "#  ", "#  ", " ##"]),
+    ('D', ["## ", "# #", "# #", "# #", "## "]),
+    ('E', ["###", "#  ", "## ", "#  ", "###"]),
+    ('F', ["###", "#  ", "## ", "#  ", "#  "]),
+    ('G', [" ##", "#  ", "# #", "# #", " ##"]),
+    ('H', ["# #", "# #", "###", "# #", "# #"]),
+    ('I', ["###", " # ", " # ", " # ", "###"]),
+    ('J', ["  #", "  #", "  #", "# #", " # "]),
+    ('K', ["# #", "# #", "## ", "# #", "# #"]),
+    ('L', ["#  ", "#  ", "#  ", "#  ", "###"]),
+    ('M', ["# #", "###", "# #", "# #", "# #"]),
+    ('N', ["## ", "# #", "# #", "# #", "# #"]),
+    ('O', ["###", "# #", "# #", "# #", "###"]),
+    ('P', ["## ", "# #", "## ", "#  ", "#  "]),
+    ('Q', [" # ", "# #", "# #", "## ", " ##"]),
+    ('R', ["## ", "# #", "## ", "# #", "# #"]),
+    ('S', [" ##", "#  ", " # ", "  #", "## "]),
+    ('T', ["###", " # ", " # ", " # ", " # "]),
+    ('U', ["# #", "# #", "# #", "# #", "###"]),
+    ('V', ["# #", "# #", "# #", "# #", " # "]),
+    ('W', ["# #", "# #", "# #", "###", "# #"]),
+    ('X', ["# #", "# #", " # ", "# #", "# #"]),
+    ('Y', ["# #", "# #", " # ", " # ", " # "]),
+    ('Z', ["###", "  #", " # ", "#  ", "###"]),
+    ('0', [" # ", "# #", "# #", "# #", " # "]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', ["## ", "  #", " # ", "#  ", "###"]),
+    ('3', ["## ", "  #", " # ", "  #", "## "]),
+    ('4', ["# #", "# #", "###", "  #", "  #"]),
+    ('5', ["###", "#  ", "## ", "  #", "## "]),
+    ('6', [" ##", "#  ", "###", "# #", "###"]),
+    ('7', ["###", "  #", " # ", " # ", " # "]),
+    ('8', ["###", "# #", "###", "# #", "###"]),
+    ('9', ["###", "# #", "###", "  #", "## "]),
+    (' ', ["   ", "   ", "   ", "   ", "   "]),
+    ('!', [" # ", " # ", " # ", "   ", " # "]),
+    ('?', ["## ", "  #", " # ", "   ", " # "]),
+    ('-', ["   ", "   ", "###", "   ", "   "]),
+    ('.', ["   ", "   ", "   ", "   ", " # "]),
+    (':', ["   ", " # ", "   ", " # ", "   "]),
+    ('\'', [" # ", " # ", "   ", "   ", "   "]),
+    ('/', ["  #", "  #", " # ", "#  ", "#  "]),
+];
+
+// FIGlet fonts list these after the printable ASCII characters
+const DEUTSCH: [char; 7] = ['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'];
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(io::Error),
+    Format(String),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io(x) => write!(f, "could not read font: {}", x),
+            FontError::Format(x) => write!(f, "not a FIGlet font: {}", x),
+        }
+    }
+}
+
+impl Error for FontError {}
+
+impl From<io::Error> for FontError {
+    fn from(error: io::Error) -> Self {
+        FontError::Io(error)
+    }
+}
+
+// Big text for titles and banners. Spaces in glyphs are transparent.
+pub struct Font {
+    height: usize,
+    glyphs: HashMap<char, Vec<String>>,
+    spacing: usize, // blank columns between characters
+    leading: usize, // blank rows between lines of text
+    uppercase: bool,
+}
+
+impl Font {
+    pub fn block() -> Self {
+        let glyphs = BLOCK_GLYPHS
+            .iter()
+            .map(|(x, rows)| {
+                let rows = rows.iter().map(|x| x.replace('#', "█")).collect();
+                (*x, rows)
+            })
+            .collect();
+        Font {
+            height: 5,
+            glyphs,
+            spacing: 1,
+            leading: 1,
+            uppercase: true,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        Font::from_flf(&fs::read_to_string(path)?)
+    }
+
+    // Characters are placed at their full width, FIGlet kerning and
+    // smushing rules are not applied
+    pub fn from_flf(text: &str) -> Result<Self, FontError> {
+        let mut lines = text.lines();
+        let header = lines
+            .next()
+            .ok_or(FontError::Format("empty file".to_string()))?;
+        let signature = header
+            .strip_prefix("flf2a")
+            .ok_or(FontError::Format("missing flf2a signature".to_string()))?;
+        let mut fields = signature.chars();
+        let hardblank = fields
+            .next()
+            .ok_or(FontError::Format("missing hardblank".to_string()))?;
+        let numbers: Vec<i64> = fields
+            .as_str()
+            .split_whitespace()
+            .map_while(|x| x.parse().ok())
+            .collect();
+        // old_layout, the fourth number, may be negative
+        let height = numbers.first().and_then(|x| usize::try_from(*x).ok());
+        let height = height.filter(|x| *x > 0);
+        let comments = numbers.get(4).map(|x| (*x).max(0) as usize);
+        let (Some(height), Some(comments)) = (height, comments) else {
+            return Err(FontError::Format("bad header".to_string()));
+        };
+        for _ in 0..comments {
+            lines.next();
+        }
+
+        let glyph = |lines: &mut std::str::Lines| -> Option<Vec<String>> {
+            let mut rows = vec![];
+            for _ in 0..height {
+                let line = lines.next()?.trim_end();
+                let endmark = line.chars().last()?;
+                let row =
+                    line.trim_end_matches(endmark).replace(hardblank, " ");
+                rows.push(row);
+            }
+            Some(rows)
+        };
+        let mut glyphs = HashMap::new();
+        for code in 32..127u8 {
+            let rows = glyph(&mut lines).ok_or(FontError::Format(format!(
+                "missing character {:?}",
+                code as char
+            )))?;
+            glyphs.insert(code as char, rows);
+        }
+        // the Deutsch characters always follow, unless the file ends here
+        for code in DEUTSCH {
+            match glyph(&mut lines) {
+                Some(rows) => glyphs.insert(code, rows),
+                None => break,
+            };
+        }
+        while let Some(tag) = lines.next() {
+            let Some(code) = tag.split_whitespace().next().and_then(parse_code)
+            else {
+                break;
+            };
+            match glyph(&mut lines) {
+                Some(rows) => glyphs.insert(code, rows),
+                None => break,
+            };
+        }
+
+        Ok(Font {
+            height,
+            glyphs,
+            spacing: 0,
+            leading: 0,
+            uppercase: false,
+        })
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn glyph(&self, character: char) -> Option<&Vec<String>> {
+        let character = if self.uppercase {
+            character.to_ascii_uppercase()
+        } else {
+            character
+        };
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&'?'))
+    }
+
+    // One line of text as `height` rows of equal width
+    pub fn render(&self, line: &str) -> Vec<String> {
+        let mut rows = vec![String::new(); self.height];
+        for (i, character) in line.chars().enumerate() {
+            let Some(glyph) = self.glyph(character) else {
+                continue;
+            };
+            let width =
+                glyph.iter().map(|x| x.chars().count()).max().unwrap_or(0);
+            for (r, row) in rows.iter_mut().enumerate() {
+                if i > 0 {
+                    row.push_str(&" ".repeat(self.spacing));
+                }
+                let part = glyph.get(r).map_or("", |x| x.as_str());
+                row.push_str(part);
+                row.push_str(&" ".repeat(width - part.chars().count()));
+            }
+        }
+        rows
+    }
+
+    // Every line of `text` is centred on its own, and the whole banner is
+    // centred vertically. Anything that does not fit is cut off.
+    pub fn draw_banner(
+        &self,
+        map: &mut Map,
+        text: &str,
+        style: impl Into<Style>,
+    ) {
+        let style = style.into();
+        let (width, height) = map.size();
+        let lines: Vec<Vec<String>> =
+            text.lines().map(|x| self.render(x)).collect();
+        let total = lines.len() * self.height
+            + lines.len().saturating_sub(1) * self.leading;
+        let mut top = (height as usize).saturating_sub(total) / 2;
+        for rows in lines {
+            let row_width = rows.first().map_or(0, |x| x.chars().count());
+            let left = (width as usize).saturating_sub(row_width) / 2;
+            for (r, row) in rows.iter().enumerate() {
+                for (c, character) in row.chars().enumerate() {
+                    if character != ' ' {
+                        map.paint(
+                            ((left + c) as u16, (top + r) as u16),
+                            character,
+                            style,
+                            Layer::Hud,
+                        );
+                    }
+                }
+            }
+            top += self.height + self.leading;
+        }
+    }
+}
+
+// Code tags are decimal, 0x hexadecimal or 0 octal
+fn parse_code(tag: &str) -> Option<char> {
+    let (digits, radix) = if let Some(x) = tag.strip_prefix("0x") {
+        (x, 16)
+    } else if let Some(x) = tag.strip_prefix("0X") {
+        (x, 16)
+    } else if tag.len() > 1 && tag.starts_with('0') {
+        (&tag[1..], 8)
+    } else {
+        (tag, 10)
+    };
+    char::from_u32(u32::from_str_radix(digits, radix).ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_bad_height_is_a_format_error() {
+        for header in ["flf2a$ -1 5 16 15 0", "flf2a$ 0 5 16 15 0"] {
+            let error = Font::from_flf(header).err();
+            assert!(matches!(error, Some(FontError::Format(_))), "{}", header);
+        }
+    }
+
+    #[test]
+    fn a_font_missing_characters_is_a_format_error() {
+        let text = "flf2a$ 1 1 4 0 0\n @\n!@\n";
+        let error = Font::from_flf(text).err();
+        assert!(matches!(error, Some(FontError::Format(_))));
+    }
+}
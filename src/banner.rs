@@ -0,0 +1,45 @@
+use crate::engine::style::Style;
+use crate::engine::ui::font::Font;
+use crate::engine::{Entity, World};
+use serde::{Deserialize, Serialize};
+
+// Big text over the middle of the playfield, shown for a while or until
+// the game ends
+#[derive(Serialize, Deserialize)]
+pub struct Banner {
+    text: String,
+    style: Style,
+    remaining: Option<f64>,
+    #[serde(skip, default = "Font::block")]
+    font: Font,
+}
+
+impl Banner {
+    pub fn new(text: &str, style: Style) -> Self {
+        Banner {
+            text: text.to_string(),
+            style,
+            remaining: None,
+            font: Font::block(),
+        }
+    }
+
+    pub fn lasting(mut self, seconds: f64) -> Self {
+        self.remaining = Some(seconds);
+        self
+    }
+}
+
+impl Entity for Banner {
+    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= delta;
+            if *remaining <= 0.0 {
+                world.remove_entity(id);
+                return;
+            }
+        }
+        self.font
+            .draw_banner(&mut world.map, &self.text, self.style.bold());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::engine::{Entity, Layer, World};
+use crate::projectile::ProjectileKind;
+use crate::sprites;
+use crate::theme;
+use crate::tuning;
+use crate::{Align, Alignment, Health, Hitboxes, Position};
+use crossterm::style::Color;
+use serde::{Deserialize, Serialize};
+
+const GUNS: [(u16, u16); 2] = [(0, 1), (4, 1)];
+const CORE: (u16, u16) = (2, 0);
+const FRENZY_TINT: f64 = 0.2;
+
+fn part_damage(part: char) -> f64 {
+    match part {
+        '/' | '\\' => 0.0, // armor plating
+        'O' => 3.0,        // exposed core
+        _ => 1.0,
+    }
+}
+
+// Lets the HUD find the boss and scale its health bar
+#[derive(Serialize, Deserialize)]
+pub struct BossBar {
+    pub max_hp: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum Phase {
+    Volley,
+    Swarm,
+    Frenzy,
+}
+
+impl Phase {
+    fn from_health(fraction: f64) -> Phase {
+        if fraction > 2.0 / 3.0 {
+            Phase::Volley
+        } else if fraction > 1.0 / 3.0 {
+            Phase::Swarm
+        } else {
+            Phase::Frenzy
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Boss {
+    position: (u16, u16), // top left of the sprite
+    tilt: f64,
+    direction: i8,
+    phase: Phase,
+    attack: f64,
+}
+
+impl Boss {
+    pub fn new(position: (u16, u16)) -> Self {
+        Boss {
+            position,
+            tilt: 0.0,
+            direction: 1,
+            phase: Phase::Volley,
+            attack: 0.0, // set on start
+        }
+    }
+
+    fn offset(&self, offset: (u16, u16)) -> (u16, u16) {
+        (self.position.0 + offset.0, self.position.1 + offset.1)
+    }
+
+    fn fire(&self, world: &mut World) {
+        let guns: Vec<(u16, u16)> =
+            GUNS.iter().map(|x| self.offset((x.0, x.1 + 1))).collect();
+        match self.phase {
+            Phase::Volley => {
+                for gun in guns {
+                    for drift in [-0.4, 0.0, 0.4] {
+                        ProjectileKind::Shot.spawn(world, gun, drift, false);
+                    }
+                }
+            }
+            Phase::Swarm => {
+                ProjectileKind::ZigZag.spawn(world, guns[0], 0.0, false);
+                ProjectileKind::Homing.spawn(world, guns[1], 0.0, false);
+            }
+            Phase::Frenzy => {
+                let below_core = self.offset((CORE.0, CORE.1 + 2));
+                ProjectileKind::Laser.spawn(world, below_core, 0.0, false);
+                ProjectileKind::Bomb.spawn(world, guns[0], -0.3, false);
+                ProjectileKind::Bomb.spawn(world, guns[1], 0.3, false);
+            }
+        }
+    }
+}
+
+impl Entity for Boss {
+    fn start(&mut self, world: &mut World, id: i64) {
+        world.set_component(
+            id,
+            Align {
+                alignment: Alignment::Enemy,
+            },
+        );
+        let tuning = tuning::get(world);
+        self.attack = tuning.boss_attack_intervals[0];
+        world.set_component(id, Health { hp: tuning.boss_hp });
+        world.set_component(
+            id,
+            BossBar {
+                max_hp: tuning.boss_hp,
+            },
+        );
+    }
+    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
+        let sprite = sprites::boss(&theme::look(world, "actors.boss"));
+        // collisions are checked against last tick's tiles, so the hitboxes
+        // have to describe where the boss was drawn, not where it moves to
+        let parts = sprite
+            .placed(self.position)
+            .into_iter()
+            .map(|(position, part, _)| (position, part_damage(part)))
+            .collect();
+        world.set_component(id, Hitboxes { parts });
+
+        let tuning = tuning::get(world);
+        let hp = world.get_component::<Health>(id).map_or(0.0, |x| x.hp);
+        let phase = Phase::from_health(hp / tuning.boss_hp);
+        if phase != self.phase {
+            // give the player a breather between phases
+            self.phase = phase;
+            log::info!("boss at {} hp enters {:?}", hp, phase);
+            self.attack = tuning.boss_attack_intervals[phase.index()];
+            if phase == Phase::Frenzy {
+                world.post.tint = Some((Color::DarkRed, FRENZY_TINT));
+            }
+        }
+
+        let speed = tuning.boss_speeds[self.phase.index()];
+        self.tilt += self.direction as f64 * speed * delta;
+        if self.tilt >= 1.0 {
+            self.tilt -= 1.0;
+            if self.position.0 + sprite.width() >= world.map.size().0 - 1 {
+                self.direction = -1;
+            } else {
+                self.position.0 += 1;
+            }
+        } else if self.tilt <= -1.0 {
+            self.tilt += 1.0;
+            if self.position.0 <= 1 {
+                self.direction = 1;
+            } else {
+                self.position.0 -= 1;
+            }
+        }
+
+        self.attack -= delta;
+        if self.attack <= 0.0 {
+            self.attack = tuning.boss_attack_intervals[self.phase.index()];
+            self.fire(world);
+        }
+
+        world
+            .map
+            .draw_sprite(self.position, &sprite, Layer::Actors, id);
+        world.set_component(id, Position(self.offset(CORE)));
+    }
+}
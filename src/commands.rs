@@ -0,0 +1,150 @@
+use crate::boss::Boss;
+use crate::engine::console::{Command, Console};
+use crate::engine::World;
+use crate::layout::{Layout, LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::tuning::{self, Tuning};
+use crate::wave::Wave;
+use crate::{
+    find_player, Align, Alignment, EnemyMotion, Plibble, Plibbler, Shootler,
+};
+use core::mem::discriminant as tag;
+use serde::{Deserialize, Serialize};
+
+const ENEMIES: [&str; 4] = ["plibble", "plibbler", "shootler", "boss"];
+
+// The player shrugs off every hit while it has this
+#[derive(Serialize, Deserialize)]
+pub struct God;
+
+pub fn register(console: &mut Console) {
+    let names = Tuning::default().names();
+    let names: Vec<&str> = names.iter().map(|x| x.as_str()).collect();
+    console
+        .register(
+            Command::new("spawn", "spawn <enemy> <x> <y>", spawn)
+                .completing(&ENEMIES),
+        )
+        .register(Command::new("god", "god", god))
+        .register(Command::new("wave", "wave <number>", wave))
+        .register(
+            Command::new("kill", "kill all enemies | kill <id>", kill)
+                .completing(&["all", "enemies"]),
+        )
+        .register(
+            Command::new("set", "set <NAME> [value]", set).completing(&names),
+        );
+}
+
+// At a layout position, so the same command works on any map size
+fn spawn(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let [kind, x, y] = args else {
+        return Err("usage: spawn <enemy> <x> <y>".into());
+    };
+    let position = match (x.parse::<u16>(), y.parse::<u16>()) {
+        (Ok(x), Ok(y)) if x < LAYOUT_WIDTH && y < LAYOUT_HEIGHT => (x, y),
+        _ => {
+            return Err(format!(
+                "x and y go up to {} and {}",
+                LAYOUT_WIDTH - 1,
+                LAYOUT_HEIGHT - 1
+            ))
+        }
+    };
+    let layout = Layout::of(world);
+    let motion = EnemyMotion {
+        position: layout.at(position),
+        tilt: (0.0, 0.0),
+        target: (1, 0),
+        bounds: layout.bounds((2, 22)),
+    };
+    let reload = tuning::get(world).plibbler_reload_time;
+    let id = match *kind {
+        "plibble" => world.add_entity(Plibble { motion }),
+        "plibbler" => world.add_entity(Plibbler { motion, reload }),
+        "shootler" => world.add_entity(Shootler { motion }),
+        "boss" => world.add_entity(Boss::new(layout.at(position))),
+        _ => {
+            return Err(format!(
+                "no enemy called {}, try {}",
+                kind,
+                ENEMIES.join(", ")
+            ))
+        }
+    };
+    Ok(format!("spawned {} #{}", kind, id))
+}
+
+fn god(world: &mut World, _args: &[&str]) -> Result<String, String> {
+    let player = find_player(world).ok_or("there is no player")?;
+    if world.get_component::<God>(player).is_some() {
+        world.remove_component::<God>(player);
+        Ok("god mode off".into())
+    } else {
+        world.set_component(player, God);
+        Ok("god mode on".into())
+    }
+}
+
+// Clears the current wave, the director then brings on the one asked for
+fn wave(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let number = match args {
+        [x] => x.parse::<u32>().ok().filter(|x| *x > 0),
+        _ => None,
+    }
+    .ok_or("usage: wave <number>, from 1")?;
+    let director = world.with_component::<Wave>().first().copied();
+    let wave = director
+        .and_then(|x| world.get_component::<Wave>(x))
+        .ok_or("there is no wave director")?;
+    wave.number = number - 1;
+    kill_enemies(world);
+    Ok(format!("wave {} is next", number))
+}
+
+fn kill(world: &mut World, args: &[&str]) -> Result<String, String> {
+    match args {
+        ["all", "enemies"] => {
+            Ok(format!("killed {} enemies", kill_enemies(world)))
+        }
+        [id] => {
+            let id = id.parse().map_err(|_| "usage: kill <id>")?;
+            if !world.is_alive(id) {
+                return Err(format!("there is no #{}", id));
+            }
+            world.remove_entity(id);
+            Ok(format!("killed #{}", id))
+        }
+        _ => Err("usage: kill all enemies | kill <id>".into()),
+    }
+}
+
+fn kill_enemies(world: &mut World) -> usize {
+    let enemies: Vec<i64> = world
+        .with_component::<Align>()
+        .into_iter()
+        .filter(|x| {
+            world
+                .get_component::<Align>(*x)
+                .is_some_and(|x| tag(&x.alignment) == tag(&Alignment::Enemy))
+        })
+        .collect();
+    for id in enemies.iter() {
+        world.remove_entity(*id);
+    }
+    enemies.len()
+}
+
+// Without a value it shows the current one
+fn set(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let Some((name, value)) = args.split_first() else {
+        return Err("usage: set <NAME> [value]".into());
+    };
+    let tuning = world.resource_mut::<Tuning>().ok_or("there is no tuner")?;
+    if !value.is_empty() {
+        tuning.set(name, &value.join(" "))?;
+    }
+    let value = tuning
+        .value(name)
+        .ok_or(format!("no such value {}", name))?;
+    Ok(format!("{} = {}", name.to_uppercase(), value))
+}
@@ -0,0 +1,84 @@
+use crate::engine::particles::ParticleStyle;
+use crate::engine::World;
+use crossterm::style::Color;
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+const HIT_FLASH_TIME: f64 = 0.3;
+const HIT_SHAKE: u16 = 1; // tiles
+const HIT_SHAKE_TIME: f64 = 0.4;
+
+fn explosion() -> ParticleStyle {
+    ParticleStyle {
+        glyphs: vec!['✶', '*', '+', '·'],
+        colors: vec![
+            Color::Rgb {
+                r: 255,
+                g: 240,
+                b: 120,
+            },
+            Color::Rgb {
+                r: 255,
+                g: 120,
+                b: 0,
+            },
+            Color::Rgb { r: 90, g: 0, b: 0 },
+        ],
+        lifetime: (0.3, 0.7),
+        speed: (3.0, 7.0),
+        direction: 0.0,
+        spread: TAU,
+        gravity: 0.0,
+    }
+}
+
+fn muzzle_flash() -> ParticleStyle {
+    ParticleStyle {
+        glyphs: vec!['\'', '.'],
+        colors: vec![Color::White, Color::DarkYellow],
+        lifetime: (0.05, 0.15),
+        speed: (4.0, 8.0),
+        direction: -FRAC_PI_2,
+        spread: 1.2,
+        gravity: 0.0,
+    }
+}
+
+fn debris() -> ParticleStyle {
+    ParticleStyle {
+        glyphs: vec!['▪', ',', '.'],
+        colors: vec![Color::Yellow, Color::DarkYellow],
+        lifetime: (0.4, 0.8),
+        speed: (2.0, 5.0),
+        direction: -FRAC_PI_2,
+        spread: 2.5,
+        gravity: 12.0,
+    }
+}
+
+pub fn explode(world: &mut World, position: (u16, u16)) {
+    world
+        .particles
+        .burst(&mut world.rng, position, 10, &explosion());
+}
+
+pub fn flash(world: &mut World, position: (u16, u16)) {
+    world
+        .particles
+        .burst(&mut world.rng, position, 3, &muzzle_flash());
+}
+
+pub fn crumble(world: &mut World, position: (u16, u16)) {
+    world
+        .particles
+        .burst(&mut world.rng, position, 5, &debris());
+}
+
+// Screen feedback for the player taking a hit, a shield hit only flashes
+pub fn player_hit(world: &mut World, shielded: bool) {
+    if shielded {
+        world.post.flash(Color::Blue, HIT_FLASH_TIME);
+    } else {
+        world.post.flash(Color::Red, HIT_FLASH_TIME);
+        world.post.shake(HIT_SHAKE, HIT_SHAKE_TIME);
+    }
+}
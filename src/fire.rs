@@ -0,0 +1,123 @@
+use crate::engine::animation::Animator;
+use crate::engine::{Entity, World};
+use crate::projectile::ProjectileKind;
+use crate::tuning;
+use crate::{find_player, Align, Alignment, Position};
+use core::mem::discriminant as tag;
+use serde::{Deserialize, Serialize};
+
+// Enemies that are allowed to shoot carry one of these; the FireDirector
+// decides which of them actually fires
+#[derive(Serialize, Deserialize)]
+pub struct Gunner {
+    pub kind: ProjectileKind,
+    pub reload_time: f64,
+    pub cooldown: f64,
+}
+
+impl Gunner {
+    pub fn new(kind: ProjectileKind, reload_time: f64) -> Self {
+        Gunner {
+            kind,
+            reload_time,
+            cooldown: reload_time,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FireDirector {
+    pub timer: f64,
+    pub bullets: Vec<i64>,
+}
+
+impl FireDirector {
+    pub fn new(interval: f64) -> Self {
+        FireDirector {
+            timer: interval,
+            bullets: vec![],
+        }
+    }
+
+    fn player_column(world: &mut World) -> Option<u16> {
+        let player_id = find_player(world)?;
+        world.get_component::<Position>(player_id).map(|x| x.0 .0)
+    }
+
+    // Only the bottom-most enemy in each column has a clear line of fire
+    fn front_rank(world: &mut World) -> Vec<(i64, (u16, u16))> {
+        let mut enemies: Vec<(i64, (u16, u16))> = vec![];
+        for id in world.with_component::<Position>() {
+            let is_enemy = world
+                .get_component::<Align>(id)
+                .is_some_and(|x| tag(&x.alignment) == tag(&Alignment::Enemy));
+            if let (true, Some(position)) =
+                (is_enemy, world.get_component::<Position>(id))
+            {
+                enemies.push((id, position.0));
+            }
+        }
+        enemies
+            .iter()
+            .filter(|(_, (x, y))| {
+                !enemies.iter().any(|(_, (ox, oy))| ox == x && oy > y)
+            })
+            .copied()
+            .collect()
+    }
+}
+
+impl Entity for FireDirector {
+    fn update(&mut self, delta: f64, world: &mut World, _id: i64) {
+        for gunner_id in world.with_component::<Gunner>() {
+            if let Some(gunner) = world.get_component::<Gunner>(gunner_id) {
+                gunner.cooldown -= delta;
+            }
+        }
+
+        let tuning = tuning::get(world);
+        self.bullets.retain(|x| world.is_alive(*x));
+        if self.timer > 0.0 {
+            self.timer -= delta;
+            return;
+        }
+        if self.bullets.len() >= tuning.max_enemy_bullets {
+            return;
+        }
+
+        let mut shooters = FireDirector::front_rank(world);
+        shooters.retain(|(id, _)| {
+            world
+                .get_component::<Gunner>(*id)
+                .is_some_and(|x| x.cooldown <= 0.0)
+        });
+        if shooters.is_empty() {
+            return;
+        }
+
+        let player_column = FireDirector::player_column(world);
+        let chosen = match player_column {
+            Some(column) if world.rng.chance(tuning.aimed_shot_chance) => {
+                *shooters
+                    .iter()
+                    .min_by_key(|(_, (x, _))| x.abs_diff(column))
+                    .unwrap()
+            }
+            _ => shooters[world.rng.index(shooters.len())],
+        };
+
+        let (shooter_id, (x, y)) = chosen;
+        let kind = match world.get_component::<Gunner>(shooter_id) {
+            Some(gunner) => {
+                gunner.cooldown = gunner.reload_time;
+                gunner.kind
+            }
+            None => return,
+        };
+        self.bullets.push(kind.spawn(world, (x, y + 1), 0.0, false));
+        if let Some(animator) = world.get_component::<Animator>(shooter_id) {
+            animator.restart("fire");
+        }
+        self.timer = tuning.enemy_fire_interval;
+    }
+}
@@ -0,0 +1,87 @@
+use crate::boss::BossBar;
+use crate::engine::{Entity, World};
+use crate::power_up::PowerUps;
+use crate::theme;
+use crate::tuning::TuningError;
+use crate::wave::Wave;
+use crate::{find_player, Health, BOSS_BAR_WIDTH};
+use crossterm::{style::Color, terminal};
+use serde::{Deserialize, Serialize};
+
+// Status line drawn just below the playfield, with the boss health bar
+// underneath it while a boss is alive
+#[derive(Serialize, Deserialize)]
+pub struct Hud;
+
+impl Hud {
+    fn draw_line(world: &mut World, line: u16, segments: Vec<(String, Color)>) {
+        let _ = world.ui.clear_line(line);
+        let mut column = 0;
+        for (text, color) in segments {
+            let _ = world.ui.text_draw((column, line), &text, color);
+            column += text.chars().count() as u16 + 1;
+        }
+    }
+
+    fn boss_bar(world: &mut World) -> Vec<(String, Color)> {
+        let Some(boss_id) = world.with_component::<BossBar>().first().copied()
+        else {
+            return vec![];
+        };
+        let max_hp = world.get_component::<BossBar>(boss_id).unwrap().max_hp;
+        let hp = world.get_component::<Health>(boss_id).map_or(0.0, |x| x.hp);
+        let filled = ((hp / max_hp).clamp(0.0, 1.0) * BOSS_BAR_WIDTH as f64)
+            .ceil() as usize;
+        let text = theme::look(world, "hud.text");
+        let bar = theme::look(world, "hud.boss_bar");
+        vec![
+            ("BOSS".to_string(), text.color),
+            ("█".repeat(filled), bar.color),
+            ("░".repeat(BOSS_BAR_WIDTH - filled), bar.accent),
+        ]
+    }
+}
+
+impl Entity for Hud {
+    fn update(&mut self, _delta: f64, world: &mut World, _id: i64) {
+        let text = theme::look(world, "hud.text").color;
+        let mut segments = vec![];
+        if let Some(wave_id) = world.with_component::<Wave>().first() {
+            let number = world.get_component::<Wave>(*wave_id).unwrap().number;
+            segments.push((format!("Wave {}", number), text));
+        }
+        match find_player(world) {
+            Some(player_id) => {
+                let lives = world
+                    .get_component::<Health>(player_id)
+                    .map_or(0.0, |x| x.hp);
+                segments.push((format!("Lives: {}", lives), text));
+                let active = world
+                    .get_component::<PowerUps>(player_id)
+                    .map_or(vec![], |x| x.active.clone());
+                for (kind, remaining) in active {
+                    segments.push((
+                        format!("{} {:.0}s", kind.label(), remaining.ceil()),
+                        theme::look(world, kind.look_key()).color,
+                    ));
+                }
+            }
+            None => segments.push(("Lives: 0".to_string(), text)),
+        }
+
+        let line = world.screen_size().1;
+        Hud::draw_line(world, line, segments);
+        let mut segments = Hud::boss_bar(world);
+        // a broken tuning file is reported here rather than stopping the game
+        if let Some(tuner) = world.with_component::<TuningError>().first() {
+            let used: usize =
+                segments.iter().map(|(x, _)| x.chars().count() + 1).sum();
+            let width = terminal::size().map_or(80, |x| x.0) as usize;
+            let error = world.get_component::<TuningError>(*tuner).unwrap();
+            let text = error.0.chars().take(width.saturating_sub(used));
+            let text: String = text.collect();
+            segments.push((text, Color::Red));
+        }
+        Hud::draw_line(world, line + 1, segments);
+    }
+}
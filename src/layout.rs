@@ -0,0 +1,42 @@
+use crate::engine::World;
+
+// Positions in level layouts are written for a 25x15 playfield, which is
+// also the smallest map the game runs on
+pub const LAYOUT_WIDTH: u16 = 25;
+pub const LAYOUT_HEIGHT: u16 = 15;
+
+// Maps layout positions onto the actual map. Columns are stretched across
+// the width, rows in the top half stay where they are and rows in the
+// bottom half keep their distance from the bottom wall.
+#[derive(Clone, Copy)]
+pub struct Layout {
+    size: (u16, u16),
+}
+
+impl Layout {
+    pub fn of(world: &World) -> Self {
+        Layout {
+            size: world.map.size(),
+        }
+    }
+
+    pub fn column(&self, x: u16) -> u16 {
+        (x as u32 * (self.size.0 - 1) as u32 / (LAYOUT_WIDTH - 1) as u32) as u16
+    }
+
+    pub fn row(&self, y: u16) -> u16 {
+        if y < LAYOUT_HEIGHT / 2 {
+            y
+        } else {
+            self.size.1 - (LAYOUT_HEIGHT - y)
+        }
+    }
+
+    pub fn at(&self, position: (u16, u16)) -> (u16, u16) {
+        (self.column(position.0), self.row(position.1))
+    }
+
+    pub fn bounds(&self, bounds: (u16, u16)) -> (u16, u16) {
+        (self.column(bounds.0), self.column(bounds.1))
+    }
+}
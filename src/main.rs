@@ -1,111 +1,391 @@
-use std::{os::linux::raw::stat, vec};
 extern crate engine;
-use crate::engine::{Entity, World};
+use crate::engine::animation::Animator;
+use crate::engine::charset::Charset;
+use crate::engine::logging::FileLogger;
+use crate::engine::overlay::OVERLAY_LINES;
+use crate::engine::replay::Replay;
+use crate::engine::{Entity, Layer, RenderMode, World};
+use banner::Banner;
+use commands::God;
 use core::mem::discriminant as tag;
-use crossterm::{cursor::position, event::KeyCode};
-const MAP_HEIGHT: u16 = 15;
-const MAP_WIDTH: u16 = 25; // in characters
-const BULLET_SPEED: f64 = 5.0;
-const PLAYER_SPEED: f64 = 4.5; // characters per second
-const PLAYER_RELOAD_TIME: f64 = 0.3;
-const PLIBBLE_SPEED: f64 = 2.0;
-const PLIBBLER_RELOAD_TIME: f64 = 3.0;
-const PLIBBLER_SPEED: f64 = 1.5;
-const SHOOTLER_SPEED: f64 = 1.0;
-const SHOOTLER_RELOAD_TIME: f64 = 2.0;
+use crossterm::{event::KeyCode, terminal};
+use fire::{FireDirector, Gunner};
+use hud::Hud;
+use layout::{Layout, LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use power_up::{PowerUpKind, PowerUps};
+use projectile::ProjectileKind;
+use serde::{Deserialize, Serialize};
+use std::{env, path::PathBuf, process};
+use theme::{Kind, Theme, ThemeSwitcher};
+use tuning::{Tuner, Tuning};
+use wave::WaveDirector;
+mod banner;
+mod boss;
+mod commands;
+mod effects;
+mod fire;
+mod hud;
+mod layout;
+mod power_up;
+mod projectile;
+mod saves;
+mod sprites;
+#[cfg(test)]
+mod tests;
+mod theme;
+mod tuning;
+mod wave;
+const HUD_LINES: u16 = 2;
+const FADE_IN_TIME: f64 = 1.0;
+const WAVE_BANNER_TIME: f64 = 1.5;
+const WAVE_FADE_TIME: f64 = 0.5;
+const GAME_OVER_FADE_TIME: f64 = 1.0;
+const QUICKSAVE_FILE: &str = "quicksave.json";
+const REWIND_SECONDS: f64 = 10.0;
+const LOG_FILTER: &str = "info";
+const TUNING_FILE: &str = "tuning.toml";
+const BOSS_BAR_WIDTH: usize = 20;
 
 fn main() {
-    let mut world = World::new(MAP_WIDTH as usize, MAP_HEIGHT as usize);
+    let mut args: Vec<String> = env::args().collect();
+    if let Some(path) = option(&args, "--log") {
+        let filter = option(&args, "--log-level").unwrap_or(LOG_FILTER);
+        if let Err(error) =
+            FileLogger::new(path, filter).and_then(|x| x.install())
+        {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+    // the save could change or go before the replay is played, and then
+    // the replay would no longer play back the same
+    if option(&args, "--record").is_some() && option(&args, "--load").is_some()
+    {
+        eprintln!("--record can't be used with --load");
+        process::exit(1);
+    }
+    // a replay brings back the options it was recorded with
+    let replay = option(&args, "--replay").map(Replay::load).transpose();
+    let replay = match replay {
+        Ok(x) => x,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+    if let Some(replay) = &replay {
+        args.truncate(1);
+        args.extend(replay.args.iter().cloned());
+    }
+    let render_mode = if args.iter().any(|x| x == "--pixels") {
+        RenderMode::HalfBlock
+    } else {
+        RenderMode::Characters
+    };
+    let (width, height) = map_size(&args, render_mode);
+    let (themes, theme) = match themes(&args) {
+        Ok(x) => x,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+    let tuner = match tuner(&args) {
+        Ok(x) => x,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+    let mut world = World::new(width as usize, height as usize);
+    world.render_mode = render_mode;
+    if let Some(charset) = option(&args, "--charset").and_then(Charset::parse) {
+        world.ui.charset = charset;
+    }
+    // the overlay goes under the HUD
+    world.overlay.line = HUD_LINES;
+    world.overlay.visible = args.iter().any(|x| x == "--debug");
+    // a Chrome trace of the whole run, written on quitting
+    if let Some(path) = option(&args, "--profile") {
+        world.profiler.trace(path);
+    }
+    world.post.enabled = !args.iter().any(|x| x == "--no-effects");
+    // 0 keeps the fades but stops the shaking and flashing
+    if let Some(intensity) =
+        option(&args, "--effects-intensity").and_then(|x| x.parse::<f64>().ok())
+    {
+        world.post.intensity = intensity.max(0.0);
+    }
+    world.post.fade_in(FADE_IN_TIME);
+    if let Some(replay) = replay {
+        world.play(replay);
+    } else if let Some(path) = option(&args, "--record") {
+        let tuning = tuner.tuning();
+        world.record(path, recorded_args(&args, (width, height), tuning));
+    }
+    world.registry = saves::registry(themes.clone());
+    populate(&mut world, themes, theme, tuner);
+    if args.iter().any(|x| x == "--rewind") {
+        let seconds = option(&args, "--rewind").and_then(|x| x.parse().ok());
+        world.enable_rewind(seconds.unwrap_or(REWIND_SECONDS));
+    }
+    let quicksave = option(&args, "--save-file").unwrap_or(QUICKSAVE_FILE);
+    world.quicksave = Some(quicksave.into());
+    commands::register(&mut world.console);
+    // replaces everything set up above that the save knows about
+    if let Some(path) = option(&args, "--load") {
+        if let Err(error) = world.load(path) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    }
+    if let Err(error) = world.init() {
+        eprintln!("{}", error);
+    }
+}
+
+// Everything a new game starts with
+fn populate(world: &mut World, themes: Vec<Theme>, theme: usize, tuner: Tuner) {
+    let layout = Layout::of(world);
+    // first, so the theme is in place before anything else starts
+    world.add_entity(ThemeSwitcher::new(themes, theme));
+    let tuning = *tuner.tuning();
+    world.add_entity(tuner);
     world.add_entity(Ship {
-        position: (12, 13),
+        position: layout.at((12, 13)),
         tilt: (0.0, 0.0),
         target: (0, 0),
-        reload: PLAYER_RELOAD_TIME,
-    });
-    world.add_entity(Plibbler {
-        motion: EnemyMotion {
-            position: (3, 1),
-            tilt: (0.0, 0.0),
-            target: (1, 0),
-            bounds: (1, 11),
-        },
-        reload: PLIBBLER_RELOAD_TIME,
-    });
-    world.add_entity(Plibbler {
-        motion: EnemyMotion {
-            position: (21, 1),
-            tilt: (0.0, 0.0),
-            target: (-1, 0),
-            bounds: (13, 23),
-        },
-        reload: PLIBBLER_RELOAD_TIME,
-    });
-    world.add_entity(Plibble {
-        motion: EnemyMotion {
-            position: (1, 2),
-            tilt: (0.0, 0.0),
-            target: (1, 0),
-            bounds: (1, 11),
-        },
-    });
-    world.add_entity(Plibble {
-        motion: EnemyMotion {
-            position: (23, 2),
-            tilt: (0.0, 0.0),
-            target: (-1, 0),
-            bounds: (13, 23),
-        },
-    });
-    world.add_entity(Shootler {
-        motion: EnemyMotion {
-            position: (23, 2),
-            tilt: (0.0, 0.0),
-            target: (-1, 0),
-            bounds: (13, 23),
-        },
-        reload: SHOOTLER_RELOAD_TIME,
+        reload: tuning.player_reload_time,
     });
+    world.add_entity(WaveDirector::new());
+    world.add_entity(FireDirector::new(tuning.enemy_fire_interval));
+    world.add_entity(Hud);
+
+    build_walls(world);
+
+    // barriers keep their shape and are spread out by their middle column
+    for middle in [5, 12, 19] {
+        let (x, y) = layout.at((middle, 12));
+        for position in [(x - 1, y), (x, y), (x + 1, y), (x, y - 1)] {
+            world.add_entity(Barrier { position });
+        }
+    }
+}
 
-    build_walls(&mut world);
+// The value following `flag`, as in `--charset ascii`
+fn option<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|x| x == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|x| x.as_str())
+}
 
-    world.add_entity(Barrier { position: (4, 12) });
-    world.add_entity(Barrier { position: (5, 12) });
-    world.add_entity(Barrier { position: (6, 12) });
-    world.add_entity(Barrier { position: (11, 12) });
-    world.add_entity(Barrier { position: (12, 12) });
-    world.add_entity(Barrier { position: (13, 12) });
-    world.add_entity(Barrier { position: (18, 12) });
-    world.add_entity(Barrier { position: (19, 12) });
-    world.add_entity(Barrier { position: (20, 12) });
-    world.add_entity(Barrier { position: (5, 11) });
-    world.add_entity(Barrier { position: (12, 11) });
-    world.add_entity(Barrier { position: (19, 11) });
-    let _ = world.init();
+// The options a replay needs to set the game up the same way again. The map
+// size is pinned, as `--fit` depends on the terminal, and so is the tuning,
+// as the file may have changed by the time the replay is played.
+fn recorded_args(
+    args: &[String],
+    size: (u16, u16),
+    tuning: &Tuning,
+) -> Vec<String> {
+    let mut recorded = vec![];
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--record" | "--size" | "--profile" | "--tuning" | "--tuned" => {
+                rest.next();
+            }
+            "--fit" => {}
+            _ => recorded.push(arg.clone()),
+        }
+    }
+    recorded.push("--size".to_string());
+    recorded.push(format!("{}x{}", size.0, size.1));
+    recorded.push("--tuned".to_string());
+    recorded.push(tuning.line());
+    recorded
+}
+
+// `--tuned` gives every value on one line, replays use it to play by the
+// values they were recorded with
+fn tuner(args: &[String]) -> Result<Tuner, String> {
+    match option(args, "--tuned") {
+        Some(line) => Ok(Tuner::fixed(
+            Tuning::from_line(line).map_err(|x| format!("--tuned: {}", x))?,
+        )),
+        None => Ok(Tuner::new(tuning_file(args))),
+    }
+}
+
+// `--tuning` names the file to watch, otherwise tuning.toml is watched if
+// there is one
+fn tuning_file(args: &[String]) -> Option<PathBuf> {
+    match option(args, "--tuning") {
+        Some(path) => Some(path.into()),
+        None => Some(PathBuf::from(TUNING_FILE)).filter(|x| x.exists()),
+    }
+}
+
+// `--size WIDTHxHEIGHT` or `--fit` to fill the terminal, below the
+// layout's size the layout is used as is. Half blocks fit two map rows in
+// a terminal row, so the default map is twice as tall with them.
+fn map_size(args: &[String], render_mode: RenderMode) -> (u16, u16) {
+    let requested = match option(args, "--size") {
+        Some(size) => size
+            .split_once('x')
+            .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?))),
+        None if args.iter().any(|x| x == "--fit") => {
+            terminal::size().ok().map(|(columns, rows)| {
+                let rows = rows.saturating_sub(HUD_LINES + OVERLAY_LINES);
+                match render_mode {
+                    RenderMode::Characters => (columns, rows),
+                    RenderMode::HalfBlock => (columns, rows * 2),
+                }
+            })
+        }
+        None => None,
+    };
+    let default = match render_mode {
+        RenderMode::Characters => (LAYOUT_WIDTH, LAYOUT_HEIGHT),
+        RenderMode::HalfBlock => (LAYOUT_WIDTH, LAYOUT_HEIGHT * 2),
+    };
+    let (width, height) = requested.unwrap_or(default);
+    (width.max(LAYOUT_WIDTH), height.max(LAYOUT_HEIGHT))
+}
+
+// `--theme` takes the name of a built-in theme, like high-contrast, or the
+// path to a theme file
+fn themes(args: &[String]) -> Result<(Vec<Theme>, usize), String> {
+    let mut themes = Theme::built_in();
+    let Some(choice) = option(args, "--theme") else {
+        return Ok((themes, 0));
+    };
+    if let Some(i) = themes
+        .iter()
+        .position(|x| x.slug() == choice.to_lowercase())
+    {
+        return Ok((themes, i));
+    }
+    let theme = Theme::load(choice, &themes[0])?;
+    themes.push(theme);
+    let last = themes.len() - 1;
+    Ok((themes, last))
 }
 
 fn build_walls(world: &mut World) {
-    for r in 0..MAP_WIDTH {
-        for c in 0..MAP_HEIGHT {
-            if r == 0 || c == 0 || r == MAP_WIDTH - 1 || c == MAP_HEIGHT - 1 {
+    let (width, height) = world.map.size();
+    for r in 0..width {
+        for c in 0..height {
+            if r == 0 || c == 0 || r == width - 1 || c == height - 1 {
                 world.add_entity(Wall { position: (r, c) });
             }
         }
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct Health {
     hp: f64,
 }
 
+#[derive(Serialize, Deserialize)]
+struct Position((u16, u16));
+
+// Damage multipliers for the tiles of entities bigger than one character,
+// anything not listed takes normal damage
+#[derive(Serialize, Deserialize)]
+struct Hitboxes {
+    parts: Vec<((u16, u16), f64)>,
+}
+
+impl Hitboxes {
+    fn damage_at(&self, position: (u16, u16)) -> f64 {
+        self.parts
+            .iter()
+            .find(|(x, _)| *x == position)
+            .map_or(1.0, |(_, damage)| *damage)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 enum Alignment {
     Player = 0,
     Enemy,
+    Neutral, // pickups and the like, bullets pass through
 }
 
+#[derive(Serialize, Deserialize)]
 struct Align {
     alignment: Alignment,
 }
 
+fn find_player(world: &mut World) -> Option<i64> {
+    world.with_component::<Align>().into_iter().find(|x| {
+        world
+            .get_component::<Align>(*x)
+            .is_some_and(|x| tag(&x.alignment) == tag(&Alignment::Player))
+    })
+}
+
+// Shields soak a hit, Health counts down, anything else just dies
+fn strike(world: &mut World, target: i64, at: (u16, u16)) {
+    if world.get_component::<God>(target).is_some() {
+        return;
+    }
+    let shielded = world
+        .get_component::<PowerUps>(target)
+        .is_some_and(|x| x.has(PowerUpKind::Shield));
+    if find_player(world) == Some(target) {
+        log::debug!("player hit at {:?}, shielded: {}", at, shielded);
+        effects::player_hit(world, shielded);
+    }
+    if shielded {
+        if let Some(power_ups) = world.get_component::<PowerUps>(target) {
+            power_ups.consume(PowerUpKind::Shield);
+        }
+        return;
+    }
+    let damage = world
+        .get_component::<Hitboxes>(target)
+        .map_or(1.0, |x| x.damage_at(at));
+    if let Some(health) = world.get_component::<Health>(target) {
+        health.hp -= damage;
+        if health.hp > 0.0 {
+            return;
+        }
+    }
+    if find_player(world) == Some(target) {
+        log::info!("game over");
+        world.add_entity(GameOver {
+            remaining: GAME_OVER_FADE_TIME,
+        });
+    }
+    world.remove_entity(target);
+}
+
+// Fades the game out, then back in on the GAME OVER banner
+#[derive(Serialize, Deserialize)]
+struct GameOver {
+    remaining: f64,
+}
+
+impl Entity for GameOver {
+    fn start(&mut self, world: &mut World, _id: i64) {
+        world.post.fade_out(GAME_OVER_FADE_TIME);
+    }
+    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
+        self.remaining -= delta;
+        if self.remaining > 0.0 {
+            return;
+        }
+        let look = theme::look(world, "hud.game_over");
+        world.add_entity(Banner::new("GAME\nOVER", look.style()));
+        world.post.fade_in(GAME_OVER_FADE_TIME);
+        world.remove_entity(id);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
 struct Ship {
     position: (u16, u16),
     tilt: (f64, f64),
@@ -115,7 +395,12 @@ struct Ship {
 
 impl Entity for Ship {
     fn start(&mut self, world: &mut World, id: i64) {
-        world.set_component(id, Health { hp: 10.0 });
+        let lives = tuning::get(world).player_lives;
+        world.set_component(id, Health { hp: lives });
+        world.set_component(id, PowerUps::new());
+        world.set_component(id, Kind("ship".to_string()));
+        let look = theme::look(world, "actors.ship");
+        world.set_component(id, sprites::ship_animator(&look));
         world.set_component(
             id,
             Align {
@@ -124,24 +409,6 @@ impl Entity for Ship {
         );
     }
     fn update(&mut self, delta: f64, world: &mut World, id: i64) {
-        let _ = world
-            .ui
-            .debug_draw(15, format!("Tilt: {:?}", self.tilt).as_str());
-        let _ = world.ui.debug_draw(
-            16,
-            format!("X_Position: {:?}", self.position.0).as_str(),
-        );
-        let _ = world.ui.debug_draw(
-            17,
-            format!("Last Input: {:?}", world.ui.last_input).as_str(),
-        );
-        let _ = world
-            .ui
-            .debug_draw(18, format!("Target: {:?}", self.target).as_str());
-        let _ = world
-            .ui
-            .debug_draw(19, format!("Delta: {:?}", delta).as_str());
-
         match world.ui.current_input {
             Some(KeyCode::Left) => {
                 if self.target.0 == 1 {
@@ -159,20 +426,24 @@ impl Entity for Ship {
             }
 
             Some(KeyCode::Up) => {
-                self.shoot(world);
+                self.shoot(world, id);
             }
             _ => {}
         }
 
+        let speed = tuning::get(world).player_speed;
         match self.target.0 {
-            1 => self.tilt.0 += PLAYER_SPEED * delta,
-            -1 => self.tilt.0 -= PLAYER_SPEED * delta,
+            1 => self.tilt.0 += speed * delta,
+            -1 => self.tilt.0 -= speed * delta,
             _ => {}
         }
 
         if self.reload > 0.0 {
             self.reload -= delta;
         }
+        if let Some(power_ups) = world.get_component::<PowerUps>(id) {
+            power_ups.tick(delta);
+        }
 
         if self.tilt.0 > 1.0 {
             self.position.0 += 1;
@@ -182,20 +453,20 @@ impl Entity for Ship {
             self.tilt.0 += 1.0;
         }
 
-        self.position.0 = self.position.0.clamp(1, MAP_WIDTH - 2);
-        self.position.1 = self.position.1.clamp(1, MAP_HEIGHT - 2);
+        let (width, height) = world.map.size();
+        self.position.0 = self.position.0.clamp(1, width - 2);
+        self.position.1 = self.position.1.clamp(1, height - 2);
 
-        let visual = match self.target.0 {
-            -1 => '<',
-            1 => '>',
-            _ => '^',
-        };
-        world.map.write(
-            self.position,
-            visual,
-            crossterm::style::Color::Green,
-            id,
-        );
+        world.set_component(id, Position(self.position));
+
+        if let Some(animator) = world.get_component::<Animator>(id) {
+            animator.play(match self.target.0 {
+                -1 => "left",
+                1 => "right",
+                _ => "idle",
+            });
+        }
+        world.draw_animator(id, self.position, Layer::Actors);
     }
 }
 
@@ -204,104 +475,85 @@ impl Ship {
         self.tilt = (0.0, 0.0);
         self.target = (0, 0);
     }
-    fn shoot(&mut self, world: &mut World) {
+    fn shoot(&mut self, world: &mut World, id: i64) {
         if self.reload <= 0.0 {
-            world.add_entity(Bullet {
-                position: (self.position.0, self.position.1 - 1),
-                tilt: (0.0, 0.0),
-                from_player: true,
-                color: crossterm::style::Color::DarkGreen,
-            });
-            self.reload = PLAYER_RELOAD_TIME;
-            self.zero_movement();
-        }
-    }
-}
-
-struct Bullet {
-    position: (u16, u16),
-    tilt: (f64, f64),
-    from_player: bool,
-    color: crossterm::style::Color,
-}
-
-impl Entity for Bullet {
-    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
-        self.tilt.1 += if self.from_player {
-            -delta * BULLET_SPEED
-        } else {
-            delta * BULLET_SPEED
-        };
-        if self.tilt.1 <= -1.0 {
-            self.position.1 -= 1;
-            self.tilt.1 += 1.0;
-        } else if self.tilt.1 >= 1.0 {
-            self.position.1 += 1;
-            self.tilt.1 -= 1.0;
-        }
-        if self.position.1 <= 0 || self.position.1 >= MAP_HEIGHT - 1 {
-            world.remove_entity(id);
-        } else {
-            let mut other_id = id;
-            match world.query_map(self.position).first() {
-                Some(x) => {
-                    other_id = x.id;
-                }
-                None => {}
+            let tuning = tuning::get(world);
+            let has = |world: &mut World, kind| {
+                world
+                    .get_component::<PowerUps>(id)
+                    .is_some_and(|x: &mut PowerUps| x.has(kind))
+            };
+            let kind = if has(world, PowerUpKind::Laser) {
+                ProjectileKind::Laser
+            } else if has(world, PowerUpKind::Homing) {
+                ProjectileKind::Homing
+            } else if has(world, PowerUpKind::Piercing) {
+                ProjectileKind::Piercing
+            } else {
+                ProjectileKind::Shot
+            };
+            let drifts = if has(world, PowerUpKind::Spread)
+                && kind != ProjectileKind::Laser
+            {
+                vec![-tuning.spread_drift, 0.0, tuning.spread_drift]
+            } else {
+                vec![0.0]
+            };
+            let muzzle = (self.position.0, self.position.1 - 1);
+            for drift in drifts {
+                kind.spawn(world, muzzle, drift, true);
             }
-            if other_id == id {
-                world.map.write(self.position, '*', self.color, id);
+            effects::flash(world, muzzle);
+            self.reload = if has(world, PowerUpKind::RapidFire) {
+                tuning.rapid_fire_reload_time
             } else {
-                let struck_alignment: Option<&mut Align> =
-                    world.get_component(other_id);
-                match struck_alignment {
-                    Some(x) => {
-                        if (tag(&x.alignment) == tag(&Alignment::Enemy)
-                            && self.from_player)
-                            || (tag(&x.alignment) == tag(&Alignment::Player)
-                                && !self.from_player)
-                        {
-                            world.remove_entity(id);
-                            world.remove_entity(other_id);
-                        }
-                    }
-                    None => {
-                        world.remove_entity(id);
-                        world.remove_entity(other_id);
-                    }
-                }
+                tuning.player_reload_time
+            };
+            if kind == ProjectileKind::Laser {
+                self.reload = self.reload.max(tuning.laser_duration);
             }
+            self.zero_movement();
         }
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct Barrier {
     position: (u16, u16),
 }
 
 impl Entity for Barrier {
     fn update(&mut self, _delta: f64, world: &mut World, id: i64) {
+        let look = theme::look(world, "terrain.barrier");
         world.map.write(
             self.position,
-            '#',
-            crossterm::style::Color::Yellow,
+            look.glyph(0),
+            look.style(),
+            Layer::Terrain,
             id,
         );
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct Wall {
     position: (u16, u16),
 }
 
 impl Entity for Wall {
     fn update(&mut self, _delta: f64, world: &mut World, id: i64) {
-        world
-            .map
-            .write(self.position, '#', crossterm::style::Color::White, id);
+        let look = theme::look(world, "terrain.wall");
+        world.map.write(
+            self.position,
+            look.glyph(0),
+            look.style(),
+            Layer::Terrain,
+            id,
+        );
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct EnemyMotion {
     position: (u16, u16),
     tilt: (f64, f64),
@@ -334,12 +586,11 @@ impl EnemyMotion {
             }
         }
 
-        world
-            .map
-            .write(self.position, '@', crossterm::style::Color::Red, id);
+        world.set_component(id, Position(self.position));
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct Plibble {
     motion: EnemyMotion,
 }
@@ -352,19 +603,28 @@ impl Entity for Plibble {
                 alignment: Alignment::Enemy,
             },
         );
+        let reload_time = tuning::get(world).plibble_reload_time;
+        world.set_component(
+            id,
+            Gunner::new(ProjectileKind::ZigZag, reload_time),
+        );
     }
     fn update(&mut self, delta: f64, world: &mut World, id: i64) {
-        self.motion.update(delta, world, id, PLIBBLE_SPEED);
+        let speed = tuning::get(world).plibble_speed;
+        self.motion.update(delta, world, id, speed);
 
+        let look = theme::look(world, "actors.plibble");
         world.map.write(
             self.motion.position,
-            '@',
-            crossterm::style::Color::Red,
+            look.glyph(0),
+            look.style(),
+            Layer::Actors,
             id,
         );
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct Plibbler {
     motion: EnemyMotion,
     reload: f64,
@@ -378,17 +638,30 @@ impl Entity for Plibbler {
                 alignment: Alignment::Enemy,
             },
         );
+        let reload_time = tuning::get(world).plibbler_bomb_reload_time;
+        world.set_component(id, Gunner::new(ProjectileKind::Bomb, reload_time));
+        world.set_component(id, Kind("plibbler".to_string()));
+        let look = theme::look(world, "actors.plibbler");
+        world.set_component(id, sprites::plibbler_animator(&look));
     }
     fn update(&mut self, delta: f64, world: &mut World, id: i64) {
-        self.motion.update(delta, world, id, PLIBBLER_SPEED);
+        let tuning = tuning::get(world);
+        self.motion.update(delta, world, id, tuning.plibbler_speed);
 
         if self.reload >= 0.0 {
             self.reload -= delta;
         } else {
-            self.reload = PLIBBLER_RELOAD_TIME;
+            self.reload = tuning.plibbler_reload_time;
+            // drop the Plibble just past the leading edge of the sprite
+            let ahead = (self.motion.position.0 as i32
+                + 2 * self.motion.target.0 as i32)
+                .clamp(
+                    self.motion.bounds.0 as i32,
+                    self.motion.bounds.1 as i32,
+                );
             world.add_entity(Plibble {
                 motion: EnemyMotion {
-                    position: self.motion.position,
+                    position: (ahead as u16, self.motion.position.1),
                     tilt: self.motion.tilt,
                     target: self.motion.target,
                     bounds: self.motion.bounds,
@@ -397,18 +670,13 @@ impl Entity for Plibbler {
             self.motion.tilt.0 -= self.motion.target.0 as f64;
         }
 
-        world.map.write(
-            self.motion.position,
-            '&',
-            crossterm::style::Color::Red,
-            id,
-        );
+        world.draw_animator(id, self.motion.position, Layer::Actors);
     }
 }
 
+#[derive(Serialize, Deserialize)]
 struct Shootler {
     motion: EnemyMotion,
-    reload: f64,
 }
 
 impl Entity for Shootler {
@@ -419,32 +687,16 @@ impl Entity for Shootler {
                 alignment: Alignment::Enemy,
             },
         );
+        let reload_time = tuning::get(world).shootler_reload_time;
+        world.set_component(id, Gunner::new(ProjectileKind::Shot, reload_time));
+        world.set_component(id, Kind("shootler".to_string()));
+        let look = theme::look(world, "actors.shootler");
+        world.set_component(id, sprites::shootler_animator(&look));
     }
     fn update(&mut self, delta: f64, world: &mut World, id: i64) {
-        self.motion.update(delta, world, id, SHOOTLER_SPEED);
+        let speed = tuning::get(world).shootler_speed;
+        self.motion.update(delta, world, id, speed);
 
-        if self.reload >= 0.0 {
-            self.reload -= delta;
-        } else {
-            self.reload = SHOOTLER_RELOAD_TIME;
-            world.add_entity(Bullet {
-                position: self.motion.position,
-                tilt: self.motion.tilt,
-                from_player: false,
-                color: crossterm::style::Color::DarkRed,
-            });
-            self.motion.tilt.0 -= self.motion.target.0 as f64;
-        }
-        let mut visual = 'S';
-        if self.reload > SHOOTLER_RELOAD_TIME * 0.9 {
-            visual = '$';
-        }
-
-        world.map.write(
-            self.motion.position,
-            visual,
-            crossterm::style::Color::Red,
-            id,
-        );
+        world.draw_animator(id, self.motion.position, Layer::Actors);
     }
 }
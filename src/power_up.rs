@@ -0,0 +1,172 @@
+use crate::engine::{Entity, Layer, World};
+use crate::theme;
+use crate::tuning;
+use crate::{Align, Alignment, Health};
+use core::mem::discriminant as tag;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PowerUpKind {
+    RapidFire,
+    Spread,
+    Shield,
+    Piercing,
+    Homing,
+    Laser,
+    ExtraLife,
+}
+
+impl PowerUpKind {
+    const ALL: [PowerUpKind; 7] = [
+        PowerUpKind::RapidFire,
+        PowerUpKind::Spread,
+        PowerUpKind::Shield,
+        PowerUpKind::Piercing,
+        PowerUpKind::Homing,
+        PowerUpKind::Laser,
+        PowerUpKind::ExtraLife,
+    ];
+
+    pub fn look_key(&self) -> &'static str {
+        match self {
+            PowerUpKind::RapidFire => "power_ups.rapid_fire",
+            PowerUpKind::Spread => "power_ups.spread",
+            PowerUpKind::Shield => "power_ups.shield",
+            PowerUpKind::Piercing => "power_ups.piercing",
+            PowerUpKind::Homing => "power_ups.homing",
+            PowerUpKind::Laser => "power_ups.laser",
+            PowerUpKind::ExtraLife => "power_ups.extra_life",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUpKind::RapidFire => "RAPID",
+            PowerUpKind::Spread => "SPREAD",
+            PowerUpKind::Shield => "SHIELD",
+            PowerUpKind::Piercing => "PIERCE",
+            PowerUpKind::Homing => "HOMING",
+            PowerUpKind::Laser => "LASER",
+            PowerUpKind::ExtraLife => "LIFE",
+        }
+    }
+}
+
+// Timed effects currently active on the player, kept as a component so
+// pickups and bullets can reach them without knowing about Ship
+#[derive(Serialize, Deserialize)]
+pub struct PowerUps {
+    pub active: Vec<(PowerUpKind, f64)>,
+}
+
+impl PowerUps {
+    pub fn new() -> Self {
+        PowerUps { active: vec![] }
+    }
+
+    pub fn has(&self, kind: PowerUpKind) -> bool {
+        self.active.iter().any(|(x, _)| *x == kind)
+    }
+
+    pub fn grant(&mut self, kind: PowerUpKind, duration: f64) {
+        self.consume(kind);
+        self.active.push((kind, duration));
+    }
+
+    pub fn consume(&mut self, kind: PowerUpKind) {
+        self.active.retain(|(x, _)| *x != kind);
+    }
+
+    pub fn tick(&mut self, delta: f64) {
+        for (_, remaining) in self.active.iter_mut() {
+            *remaining -= delta;
+        }
+        self.active.retain(|(_, remaining)| *remaining > 0.0);
+    }
+}
+
+pub fn maybe_drop(world: &mut World, position: (u16, u16)) {
+    let chance = tuning::get(world).power_up_drop_chance;
+    if world.rng.chance(chance) {
+        let kind = PowerUpKind::ALL[world.rng.index(PowerUpKind::ALL.len())];
+        world.add_entity(PowerUp {
+            position,
+            tilt: 0.0,
+            kind,
+        });
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PowerUp {
+    position: (u16, u16),
+    tilt: f64,
+    kind: PowerUpKind,
+}
+
+impl PowerUp {
+    fn apply(&self, world: &mut World, player_id: i64) {
+        log::debug!("picked up {}", self.kind.label());
+        match self.kind {
+            PowerUpKind::ExtraLife => {
+                if let Some(health) = world.get_component::<Health>(player_id) {
+                    health.hp += 1.0;
+                }
+            }
+            kind => {
+                let duration = tuning::get(world).power_up_duration;
+                if let Some(power_ups) =
+                    world.get_component::<PowerUps>(player_id)
+                {
+                    power_ups.grant(kind, duration);
+                }
+            }
+        }
+    }
+}
+
+impl Entity for PowerUp {
+    fn start(&mut self, world: &mut World, id: i64) {
+        world.set_component(
+            id,
+            Align {
+                alignment: Alignment::Neutral,
+            },
+        );
+    }
+    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
+        let occupants: Vec<i64> = world
+            .query_map(self.position)
+            .iter()
+            .map(|x| x.id)
+            .collect();
+        for other_id in occupants {
+            let is_player = world
+                .get_component::<Align>(other_id)
+                .is_some_and(|x| tag(&x.alignment) == tag(&Alignment::Player));
+            if is_player {
+                self.apply(world, other_id);
+                world.remove_entity(id);
+                return;
+            }
+        }
+
+        self.tilt += delta * tuning::get(world).power_up_speed;
+        if self.tilt >= 1.0 {
+            self.position.1 += 1;
+            self.tilt -= 1.0;
+        }
+        if self.position.1 >= world.map.size().1 - 1 {
+            world.remove_entity(id);
+        } else {
+            let look = theme::look(world, self.kind.look_key());
+            world.map.write(
+                self.position,
+                look.glyph(0),
+                look.style().bold(),
+                Layer::Actors,
+                id,
+            );
+        }
+    }
+}
@@ -0,0 +1,356 @@
+use crate::effects;
+use crate::engine::{Entity, Layer, World};
+use crate::power_up;
+use crate::theme;
+use crate::tuning::{self, Tuning};
+use crate::{find_player, strike, Align, Alignment, Position};
+use core::mem::discriminant as tag;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ProjectileKind {
+    Shot,
+    Bomb, // slow, splashes the tiles around the impact
+    ZigZag,
+    Homing,
+    Piercing,
+    Laser,
+}
+
+impl ProjectileKind {
+    fn look_key(&self, from_player: bool) -> &'static str {
+        match (self, from_player) {
+            (ProjectileKind::Shot, true) => "projectiles.shot",
+            (ProjectileKind::Shot, false) => "projectiles.enemy_shot",
+            (ProjectileKind::Bomb, _) => "projectiles.bomb",
+            (ProjectileKind::ZigZag, _) => "projectiles.zigzag",
+            (ProjectileKind::Homing, _) => "projectiles.homing",
+            (ProjectileKind::Piercing, _) => "projectiles.piercing",
+            (ProjectileKind::Laser, true) => "projectiles.laser",
+            (ProjectileKind::Laser, false) => "projectiles.enemy_laser",
+        }
+    }
+
+    fn speed(&self, tuning: &Tuning) -> f64 {
+        match self {
+            ProjectileKind::Bomb => tuning.bomb_speed,
+            ProjectileKind::ZigZag => tuning.zigzag_speed,
+            ProjectileKind::Homing => tuning.homing_speed,
+            _ => tuning.bullet_speed,
+        }
+    }
+
+    fn splash_radius(&self) -> u16 {
+        match self {
+            ProjectileKind::Bomb => 1,
+            _ => 0,
+        }
+    }
+
+    // `drift` is the sideways component as a fraction of the kind's speed,
+    // 0.0 fires straight up for the player and straight down for enemies
+    pub fn spawn(
+        &self,
+        world: &mut World,
+        position: (u16, u16),
+        drift: f64,
+        from_player: bool,
+    ) -> i64 {
+        let tuning = tuning::get(world);
+        if *self == ProjectileKind::Laser {
+            return world.add_entity(Laser {
+                origin: position,
+                from_player,
+                remaining: tuning.laser_duration,
+                struck: vec![],
+            });
+        }
+        let forward = if from_player { -1.0 } else { 1.0 };
+        let speed = self.speed(&tuning);
+        world.add_entity(Projectile {
+            position,
+            tilt: (0.0, 0.0),
+            velocity: (drift * speed, forward * speed),
+            kind: *self,
+            from_player,
+            age: 0.0,
+            struck: vec![],
+        })
+    }
+}
+
+// Lets beams and other shots tell projectiles apart from scenery
+#[derive(Serialize, Deserialize)]
+pub struct Missile {
+    from_player: bool,
+}
+
+fn opposes(world: &mut World, from_player: bool, target: i64) -> bool {
+    world.get_component::<Align>(target).is_some_and(|x| {
+        (tag(&x.alignment) == tag(&Alignment::Enemy) && from_player)
+            || (tag(&x.alignment) == tag(&Alignment::Player) && !from_player)
+    })
+}
+
+fn is_neutral(world: &mut World, target: i64) -> bool {
+    world
+        .get_component::<Align>(target)
+        .is_some_and(|x| tag(&x.alignment) == tag(&Alignment::Neutral))
+}
+
+fn friendly_missile(world: &mut World, from_player: bool, target: i64) -> bool {
+    world
+        .get_component::<Missile>(target)
+        .is_some_and(|x| x.from_player == from_player)
+}
+
+fn occupants(world: &mut World, position: (u16, u16)) -> Vec<i64> {
+    world.query_map(position).iter().map(|x| x.id).collect()
+}
+
+fn hit(world: &mut World, from_player: bool, target: i64, at: (u16, u16)) {
+    strike(world, target, at);
+    if !world.is_alive(target) {
+        effects::explode(world, at);
+        if from_player {
+            power_up::maybe_drop(world, at);
+        }
+    }
+}
+
+fn in_field(world: &World, position: (u16, u16)) -> bool {
+    let (width, height) = world.map.size();
+    position.0 > 0
+        && position.1 > 0
+        && position.0 < width - 1
+        && position.1 < height - 1
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Projectile {
+    position: (u16, u16),
+    tilt: (f64, f64),
+    velocity: (f64, f64), // characters per second
+    kind: ProjectileKind,
+    from_player: bool,
+    age: f64,
+    struck: Vec<i64>, // so piercing shots only hit each target once
+}
+
+impl Projectile {
+    fn steer(&mut self, world: &mut World) {
+        let tuning = tuning::get(world);
+        let speed = self.kind.speed(&tuning);
+        match self.kind {
+            ProjectileKind::ZigZag => {
+                let leg = (self.age / tuning.zigzag_period) as i64;
+                let side = if leg % 2 == 0 { 1.0 } else { -1.0 };
+                self.velocity.0 = side * tuning.zigzag_drift * speed;
+            }
+            ProjectileKind::Homing => {
+                if let Some(target) = self.target_column(world) {
+                    let offset = target as f64 - self.position.0 as f64;
+                    self.velocity.0 =
+                        (offset * tuning.homing_turn_rate).clamp(-speed, speed);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Enemy shots chase the player, player shots chase the closest enemy
+    fn target_column(&self, world: &mut World) -> Option<u16> {
+        if !self.from_player {
+            let player_id = find_player(world)?;
+            return world.get_component::<Position>(player_id).map(|x| x.0 .0);
+        }
+        let mut targets = vec![];
+        for id in world.with_component::<Position>() {
+            if opposes(world, self.from_player, id) {
+                targets.push(world.get_component::<Position>(id).unwrap().0);
+            }
+        }
+        targets
+            .into_iter()
+            .min_by_key(|x| {
+                x.0.abs_diff(self.position.0) + x.1.abs_diff(self.position.1)
+            })
+            .map(|x| x.0)
+    }
+
+    fn advance(&mut self, delta: f64) {
+        self.tilt.0 += self.velocity.0 * delta;
+        self.tilt.1 += self.velocity.1 * delta;
+        while self.tilt.0 >= 1.0 {
+            self.position.0 += 1;
+            self.tilt.0 -= 1.0;
+        }
+        while self.tilt.0 <= -1.0 {
+            self.position.0 = self.position.0.saturating_sub(1);
+            self.tilt.0 += 1.0;
+        }
+        while self.tilt.1 >= 1.0 {
+            self.position.1 += 1;
+            self.tilt.1 -= 1.0;
+        }
+        while self.tilt.1 <= -1.0 {
+            self.position.1 = self.position.1.saturating_sub(1);
+            self.tilt.1 += 1.0;
+        }
+    }
+
+    fn detonate(&self, world: &mut World, direct: i64) {
+        let radius = self.kind.splash_radius();
+        if radius == 0 {
+            hit(world, self.from_player, direct, self.position);
+            return;
+        }
+        let (width, height) = world.map.size();
+        let mut struck = vec![];
+        for x in self.position.0.saturating_sub(radius)
+            ..=(self.position.0 + radius).min(width - 1)
+        {
+            for y in self.position.1.saturating_sub(radius)
+                ..=(self.position.1 + radius).min(height - 1)
+            {
+                for other_id in occupants(world, (x, y)) {
+                    if !struck.contains(&other_id)
+                        && opposes(world, self.from_player, other_id)
+                    {
+                        struck.push(other_id);
+                        hit(world, self.from_player, other_id, (x, y));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Entity for Projectile {
+    fn start(&mut self, world: &mut World, id: i64) {
+        world.set_component(
+            id,
+            Missile {
+                from_player: self.from_player,
+            },
+        );
+    }
+    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
+        self.age += delta;
+        self.steer(world);
+        self.advance(delta);
+        if !in_field(world, self.position) {
+            world.remove_entity(id);
+            return;
+        }
+
+        let other_id = occupants(world, self.position)
+            .into_iter()
+            .find(|x| {
+                *x != id
+                    && !is_neutral(world, *x)
+                    && !friendly_missile(world, self.from_player, *x)
+                    && !self.struck.contains(x)
+            })
+            .unwrap_or(id);
+        if other_id == id {
+            let look = theme::look(world, self.kind.look_key(self.from_player));
+            world.map.write(
+                self.position,
+                look.glyph(0),
+                look.style(),
+                Layer::Projectiles,
+                id,
+            );
+        } else if world.get_component::<Align>(other_id).is_some() {
+            if opposes(world, self.from_player, other_id) {
+                let struck_enemy = self.from_player;
+                if !(self.kind == ProjectileKind::Piercing && struck_enemy) {
+                    world.remove_entity(id);
+                }
+                self.struck.push(other_id);
+                self.detonate(world, other_id);
+            }
+        } else {
+            if world.get_component::<Missile>(other_id).is_none() {
+                effects::crumble(world, self.position);
+            }
+            world.remove_entity(id);
+            world.remove_entity(other_id);
+            if self.kind.splash_radius() > 0 {
+                self.detonate(world, other_id);
+            }
+        }
+    }
+}
+
+// A beam that fills its column from the origin to the first obstacle for
+// as long as it lasts, striking everything it touches once
+#[derive(Serialize, Deserialize)]
+pub struct Laser {
+    origin: (u16, u16),
+    from_player: bool,
+    remaining: f64,
+    struck: Vec<i64>,
+}
+
+impl Entity for Laser {
+    fn start(&mut self, world: &mut World, id: i64) {
+        world.set_component(
+            id,
+            Align {
+                alignment: Alignment::Neutral,
+            },
+        );
+    }
+    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
+        self.remaining -= delta;
+        if self.remaining <= 0.0 {
+            world.remove_entity(id);
+            return;
+        }
+
+        let look = theme::look(
+            world,
+            ProjectileKind::Laser.look_key(self.from_player),
+        );
+        let mut position = self.origin;
+        while in_field(world, position) {
+            let mut blocked = false;
+            for other_id in occupants(world, position) {
+                if other_id == id
+                    || is_neutral(world, other_id)
+                    || friendly_missile(world, self.from_player, other_id)
+                {
+                    continue;
+                }
+                if world.get_component::<Missile>(other_id).is_some() {
+                    // burns straight through opposing shots
+                    world.remove_entity(other_id);
+                } else if world.get_component::<Align>(other_id).is_none() {
+                    blocked = true;
+                } else if !self.struck.contains(&other_id)
+                    && opposes(world, self.from_player, other_id)
+                {
+                    self.struck.push(other_id);
+                    hit(world, self.from_player, other_id, position);
+                }
+            }
+            if blocked {
+                break;
+            }
+            world.map.write(
+                position,
+                look.glyph(0),
+                look.style().bold(),
+                Layer::Projectiles,
+                id,
+            );
+            position.1 = if self.from_player {
+                position.1 - 1
+            } else {
+                position.1 + 1
+            };
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use crate::banner::Banner;
+use crate::boss::{Boss, BossBar};
+use crate::commands::God;
+use crate::engine::save::Registry;
+use crate::fire::{FireDirector, Gunner};
+use crate::hud::Hud;
+use crate::power_up::{PowerUp, PowerUps};
+use crate::projectile::{Laser, Missile, Projectile};
+use crate::theme::{Kind, Theme, ThemeSwitcher};
+use crate::tuning::Tuner;
+use crate::wave::{Wave, WaveDirector};
+use crate::{
+    Align, Barrier, GameOver, Health, Hitboxes, Plibble, Plibbler, Position,
+    Ship, Shootler, Wall,
+};
+
+// Everything a quicksave holds. The names end up in save files, so they
+// have to stay put when types are renamed. Animators are left out, the
+// ThemeSwitcher puts them back after a load.
+pub fn registry(themes: Vec<Theme>) -> Registry {
+    let mut registry = Registry::new();
+    registry
+        .entity_as(
+            "theme_switcher",
+            |x: &ThemeSwitcher| x.theme().slug(),
+            move |slug: String| {
+                let current =
+                    themes.iter().position(|x| x.slug() == slug).unwrap_or(0);
+                ThemeSwitcher::new(themes.clone(), current)
+            },
+        )
+        .entity::<Tuner>("tuner")
+        .entity::<Ship>("ship")
+        .entity::<WaveDirector>("wave_director")
+        .entity::<FireDirector>("fire_director")
+        .entity::<Hud>("hud")
+        .entity::<Wall>("wall")
+        .entity::<Barrier>("barrier")
+        .entity::<Plibble>("plibble")
+        .entity::<Plibbler>("plibbler")
+        .entity::<Shootler>("shootler")
+        .entity::<Boss>("boss")
+        .entity::<Projectile>("projectile")
+        .entity::<Laser>("laser")
+        .entity::<PowerUp>("power_up")
+        .entity::<Banner>("banner")
+        .entity::<GameOver>("game_over")
+        .component::<Health>("health")
+        .component::<Position>("position")
+        .component::<Hitboxes>("hitboxes")
+        .component::<Align>("align")
+        .component::<Kind>("kind")
+        .component::<Gunner>("gunner")
+        .component::<PowerUps>("power_ups")
+        .component::<Missile>("missile")
+        .component::<Wave>("wave")
+        .component::<BossBar>("boss_bar")
+        .component::<God>("god");
+    registry
+}
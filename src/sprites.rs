@@ -0,0 +1,94 @@
+use crate::engine::animation::{Animator, Clip};
+use crate::engine::sprite::Sprite;
+use crate::theme::Look;
+
+const PLIBBLER_FRAME_TIME: f64 = 0.4;
+const SHOOTLER_FIRE_TIME: f64 = 0.2;
+const ENEMY_WIDTH: usize = 3;
+
+// Enemy sprites are anchored on their middle column so `position` stays
+// the centre of the invader
+
+// The look's glyphs taken ENEMY_WIDTH at a time. Like Look::glyph, a frame
+// the theme leaves out is drawn as the first.
+fn frame(look: &Look, index: usize) -> String {
+    let frames: Vec<&[char]> = look.glyphs.chunks(ENEMY_WIDTH).collect();
+    let frame = frames.get(index).or(frames.first()).copied();
+    let frame: String = frame.unwrap_or_default().iter().collect();
+    format!("{:<1$}", frame, ENEMY_WIDTH)
+}
+
+fn plibbler(frame: &str, look: &Look) -> Sprite {
+    Sprite::with_styles(
+        &[frame],
+        &["r r"],
+        &[('r', look.accent_style())],
+        look.style(),
+    )
+    .anchored((1, 0))
+}
+
+pub fn plibbler_animator(look: &Look) -> Animator {
+    Animator::new(
+        "idle",
+        Clip::looping(vec![
+            (plibbler(&frame(look, 0), look), PLIBBLER_FRAME_TIME),
+            (plibbler(&frame(look, 1), look), PLIBBLER_FRAME_TIME),
+        ]),
+    )
+}
+
+fn shootler(frame: &str, look: &Look) -> Sprite {
+    Sprite::with_styles(
+        &[frame],
+        &["r r"],
+        &[('r', look.accent_style())],
+        look.style(),
+    )
+    .anchored((1, 0))
+}
+
+// The FireDirector restarts "fire" whenever this Shootler shoots
+pub fn shootler_animator(look: &Look) -> Animator {
+    let (idle, fire) = (frame(look, 0), frame(look, 1));
+    Animator::new("idle", Clip::still(shootler(&idle, look))).with_clip(
+        "fire",
+        Clip::once(vec![(shootler(&fire, look), SHOOTLER_FIRE_TIME)])
+            .then("idle"),
+    )
+}
+
+pub fn ship_animator(look: &Look) -> Animator {
+    let ship = |x: char| {
+        Clip::still(Sprite::new(&[x.to_string().as_str()], look.style()))
+    };
+    Animator::new("idle", ship(look.glyph(0)))
+        .with_clip("left", ship(look.glyph(1)))
+        .with_clip("right", ship(look.glyph(2)))
+}
+
+// For rebuilding an animator after the theme changes
+pub fn animator(kind: &str, look: &Look) -> Option<Animator> {
+    match kind {
+        "ship" => Some(ship_animator(look)),
+        "plibbler" => Some(plibbler_animator(look)),
+        "shootler" => Some(shootler_animator(look)),
+        _ => None,
+    }
+}
+
+// Each glyph doubles as the part name for the boss hitboxes, so themes
+// only change its colors
+pub fn boss(look: &Look) -> Sprite {
+    let core = if look.bold {
+        look.accent_style()
+    } else {
+        look.accent_style().bold()
+    };
+    Sprite::with_styles(
+        &["/=O=\\", "V   V"],
+        &["  Y  "],
+        &[('Y', core)],
+        look.style(),
+    )
+}
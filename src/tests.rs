@@ -0,0 +1,245 @@
+use crate::engine::harness::Harness;
+use crate::engine::replay::Replay;
+use crate::engine::World;
+use crate::layout::{Layout, LAYOUT_HEIGHT, LAYOUT_WIDTH};
+use crate::theme::{self, Theme, ThemeSwitcher};
+use crate::tuning::{Tuner, Tuning};
+use crate::{
+    build_walls, find_player, populate, saves, strike, EnemyMotion, Plibble,
+    Ship,
+};
+use crossterm::event::KeyCode;
+
+fn headless() -> World {
+    let mut world =
+        World::headless(LAYOUT_WIDTH as usize, LAYOUT_HEIGHT as usize);
+    // screen shake would move everything around
+    world.post.enabled = false;
+    world
+}
+
+fn game() -> Harness {
+    start(headless())
+}
+
+// For worlds that need setting up before the game goes in
+fn start(mut world: World) -> Harness {
+    world.registry = saves::registry(Theme::built_in());
+    populate(&mut world, Theme::built_in(), 0, Tuner::new(None));
+    Harness::new(world)
+}
+
+// Walls and the ship at the bottom, with no enemies
+fn arena() -> Harness {
+    let mut world = headless();
+    world.add_entity(ThemeSwitcher::new(Theme::built_in(), 0));
+    world.add_entity(Tuner::new(None));
+    build_walls(&mut world);
+    let position = Layout::of(&world).at((12, 13));
+    world.add_entity(Ship {
+        position,
+        tilt: (0.0, 0.0),
+        target: (0, 0),
+        reload: 0.0,
+    });
+    Harness::new(world)
+}
+
+#[test]
+fn first_wave_comes_in() {
+    let mut harness = game();
+    harness.run(5);
+    harness.assert_snapshot("first_wave");
+}
+
+#[test]
+fn a_save_restores_the_same_game() {
+    let mut harness = game();
+    harness.press(2, KeyCode::Left).run(5);
+    // the wave banner is still up
+    let snapshot = harness.world.snapshot().expect("the game saves");
+    let mut restored = game();
+    restored.world.restore(&snapshot).expect("the save loads");
+    assert_eq!(restored.world.snapshot().ok(), Some(snapshot));
+
+    // animations start over after a load, so the screens can differ a
+    // little, but the game goes on the same
+    harness.press(0, KeyCode::Up).run(30);
+    restored.press(0, KeyCode::Up).run(30);
+    assert_eq!(
+        restored.world.snapshot().ok(),
+        harness.world.snapshot().ok()
+    );
+}
+
+#[test]
+fn a_boss_follows_the_saved_second_wave() {
+    let mut harness = game();
+    let save = "snapshots/saves/before_the_boss.json";
+    harness.world.load(save).expect("the save loads");
+    // the break between waves, then the wave banner
+    harness.run(100);
+    assert!(harness.find("/=O=\\").is_some(), "{}", harness.screen());
+    harness.assert_snapshot("boss_wave");
+}
+
+#[test]
+fn a_replay_plays_back_the_same() {
+    let mut world = headless();
+    world.record("unused", vec![]);
+    let mut recording = start(world);
+    recording.press(5, KeyCode::Left).press(6, KeyCode::Up);
+    recording.press(30, KeyCode::Right).press(31, KeyCode::Up);
+    recording.run(150);
+    let replay = recording.world.recorded().expect("a replay").to_text();
+
+    let mut world = headless();
+    world.play(Replay::parse(&replay).expect("the replay parses"));
+    let mut playback = start(world);
+    playback.run(150);
+    assert_eq!(playback.screen(), recording.screen());
+    assert_eq!(
+        playback.world.snapshot().ok(),
+        recording.world.snapshot().ok()
+    );
+}
+
+#[test]
+fn a_save_from_a_bigger_map_is_refused() {
+    let mut harness = game();
+    harness.run(1);
+    let snapshot = harness.world.snapshot().expect("the game saves");
+    let (width, height) = (LAYOUT_WIDTH as usize, LAYOUT_HEIGHT as usize);
+    let mut smaller = World::headless(width - 1, height);
+    assert!(smaller.restore(&snapshot).is_err());
+}
+
+#[test]
+fn tuning_files_only_change_what_they_list() {
+    let tuning = Tuning::parse("player_speed = 9.0\nboss_hp = 5")
+        .expect("tuning parses");
+    let expected = Tuning {
+        player_speed: 9.0,
+        boss_hp: 5.0,
+        ..Tuning::default()
+    };
+    assert_eq!(tuning, expected);
+}
+
+#[test]
+fn bad_tuning_is_turned_away() {
+    let texts = [
+        "player_speed = -1.0",
+        "aimed_shot_chance = 1.5",
+        "player_lives = 0",
+        "boss_wave_interval = 0",
+        "boss_speeds = [1.0, 2.0]",
+        "warp_speed = 9.0",
+        "player_speed = \"fast\"",
+        "player_speed =",
+    ];
+    for text in texts {
+        assert!(Tuning::parse(text).is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn set_takes_a_toml_value_or_changes_nothing() {
+    let mut tuning = Tuning::default();
+    tuning
+        .set("BOSS_SPEEDS", "[1.0, 2.0, 4.0]")
+        .expect("a list sets");
+    assert_eq!(tuning.boss_speeds, [1.0, 2.0, 4.0]);
+    tuning
+        .set("player_speed", "6.5")
+        .expect("names are not case sensitive");
+    assert_eq!(tuning.value("PLAYER_SPEED").as_deref(), Some("6.5"));
+
+    let before = tuning;
+    for (name, value) in [
+        ("PLAYER_SPEED", "fast"),
+        ("PLAYER_SPEED", "-3.0"),
+        ("POWER_UP_DROP_CHANCE", "2.0"),
+        ("WARP_SPEED", "1.0"),
+    ] {
+        assert!(tuning.set(name, value).is_err(), "{} {}", name, value);
+    }
+    assert_eq!(tuning, before);
+}
+
+// tuning.toml has every value commented out at its default
+#[test]
+fn tuning_toml_lists_the_defaults() {
+    let text = include_str!("../tuning.toml");
+    let values: Vec<&str> = text
+        .lines()
+        .filter_map(|x| x.strip_prefix("# ").filter(|x| x.contains(" = ")))
+        .collect();
+    let tuning = Tuning::parse(&values.join("\n")).expect("tuning parses");
+    assert_eq!(tuning, Tuning::default());
+    for name in Tuning::default().names() {
+        let key = format!("{} = ", name.to_lowercase());
+        assert!(values.iter().any(|x| x.starts_with(&key)), "{}", name);
+    }
+}
+
+#[test]
+fn tuning_survives_a_replay_line() {
+    let mut tuning = Tuning::default();
+    tuning.set("PLAYER_SPEED", "0.1").expect("a number sets");
+    tuning
+        .set("ZIGZAG_DRIFT", "0.30000000000000004")
+        .expect("so does this");
+    assert_eq!(Tuning::from_line(&tuning.line()), Ok(tuning));
+}
+
+#[test]
+fn ship_moves_with_the_arrow_keys() {
+    let mut harness = arena();
+    let look = theme::look(&harness.world, "actors.ship");
+    let (idle, left) = (look.glyph(0).to_string(), look.glyph(1).to_string());
+    harness.run(1);
+    let start = harness.find(&idle).expect("ship is drawn");
+    harness.press(0, KeyCode::Left).run(20);
+    let moved = harness.find(&left).expect("ship is drawn leaning left");
+    assert!(moved.0 < start.0);
+    assert_eq!(moved.1, start.1);
+    harness.assert_snapshot("ship_moved_left");
+}
+
+#[test]
+fn game_over_fades_out_before_the_banner() {
+    let mut harness = arena();
+    harness.run(1);
+    let world = &mut harness.world;
+    let ship = find_player(world).expect("there is a ship");
+    while world.is_alive(ship) {
+        strike(world, ship, (0, 0));
+    }
+    // the banner is drawn in block letters, nothing else in the arena is
+    harness.run(10);
+    assert!(!harness.screen().contains('█'));
+    harness.run(20);
+    assert!(harness.screen().contains('█'));
+}
+
+#[test]
+fn shooting_a_plibble_removes_it() {
+    let mut harness = arena();
+    let world = &mut harness.world;
+    let glyph = theme::look(world, "actors.plibble").glyph(0).to_string();
+    let position = Layout::of(world).at((12, 5));
+    world.add_entity(Plibble {
+        motion: EnemyMotion {
+            position,
+            tilt: (0.0, 0.0),
+            target: (0, 0),
+            bounds: (position.0, position.0),
+        },
+    });
+    harness.run(1);
+    assert_eq!(harness.find(&glyph), Some(position));
+
+    harness.press(0, KeyCode::Up).run(50);
+    assert_eq!(harness.find(&glyph), None);
+}
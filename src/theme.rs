@@ -0,0 +1,221 @@
+use crate::engine::animation::Animator;
+use crate::engine::style::{parse_color, Style};
+use crate::engine::{Entity, World};
+use crate::sprites;
+use crossterm::event::KeyCode;
+use crossterm::style::Color;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
+
+const CLASSIC: &str = include_str!("../themes/classic.toml");
+const HIGH_CONTRAST: &str = include_str!("../themes/high_contrast.toml");
+const COLORBLIND: &str = include_str!("../themes/colorblind.toml");
+
+#[derive(Deserialize)]
+struct LookFile {
+    glyphs: Option<String>,
+    color: Option<String>,
+    accent: Option<String>,
+    bold: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct ThemeFile {
+    name: String,
+    #[serde(flatten)]
+    sections: HashMap<String, HashMap<String, LookFile>>,
+}
+
+// How one kind of thing is drawn
+#[derive(Clone, Debug)]
+pub struct Look {
+    pub glyphs: Vec<char>,
+    pub color: Color,
+    pub accent: Color,
+    pub bold: bool,
+}
+
+impl Look {
+    // Kinds drawn with a single character only ever use the first
+    pub fn glyph(&self, index: usize) -> char {
+        self.glyphs
+            .get(index)
+            .or(self.glyphs.first())
+            .copied()
+            .unwrap_or('?')
+    }
+
+    pub fn style(&self) -> Style {
+        self.with_weight(Style::from(self.color))
+    }
+
+    pub fn accent_style(&self) -> Style {
+        self.with_weight(Style::from(self.accent))
+    }
+
+    fn with_weight(&self, style: Style) -> Style {
+        if self.bold {
+            style.bold()
+        } else {
+            style
+        }
+    }
+}
+
+// A resource kept by the ThemeSwitcher. Looks are keyed by "section.kind",
+// as in the theme files.
+#[derive(Clone)]
+pub struct Theme {
+    pub name: String,
+    looks: HashMap<String, Look>,
+}
+
+impl Theme {
+    pub fn classic() -> Self {
+        Theme::parse(CLASSIC, None).expect("classic theme is valid")
+    }
+
+    pub fn built_in() -> Vec<Theme> {
+        let classic = Theme::classic();
+        let others = [HIGH_CONTRAST, COLORBLIND]
+            .map(|x| Theme::parse(x, Some(&classic)).expect("theme is valid"));
+        let mut themes = vec![classic];
+        themes.extend(others);
+        themes
+    }
+
+    pub fn load(path: impl AsRef<Path>, base: &Theme) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|x| format!("{}: {}", path.display(), x))?;
+        Theme::parse(&text, Some(base))
+            .map_err(|x| format!("{}: {}", path.display(), x))
+    }
+
+    // Kinds and fields the text leaves out are taken from `base`
+    pub fn parse(text: &str, base: Option<&Theme>) -> Result<Self, String> {
+        let file: ThemeFile =
+            toml::from_str(text).map_err(|x| x.to_string())?;
+        let mut looks = base.map_or(HashMap::new(), |x| x.looks.clone());
+        for (section, kinds) in file.sections {
+            for (kind, look) in kinds {
+                let key = format!("{}.{}", section, kind);
+                let color = |name: Option<String>| match name {
+                    Some(name) => parse_color(&name)
+                        .map(Some)
+                        .ok_or(format!("{}: unknown color {:?}", key, name)),
+                    None => Ok(None),
+                };
+                let color_of = color(look.color)?;
+                let accent_of = color(look.accent)?;
+                let fallback = looks.get(&key).cloned().unwrap_or(Look {
+                    glyphs: vec![],
+                    color: Color::White,
+                    accent: Color::White,
+                    bold: false,
+                });
+                looks.insert(
+                    key.clone(),
+                    Look {
+                        glyphs: look
+                            .glyphs
+                            .map_or(fallback.glyphs, |x| x.chars().collect()),
+                        color: color_of.unwrap_or(fallback.color),
+                        accent: accent_of.unwrap_or(fallback.accent),
+                        bold: look.bold.unwrap_or(fallback.bold),
+                    },
+                );
+            }
+        }
+        Ok(Theme {
+            name: file.name,
+            looks,
+        })
+    }
+
+    // The name with spaces as dashes, for picking a theme on the command line
+    pub fn slug(&self) -> String {
+        self.name.to_lowercase().replace(' ', "-")
+    }
+
+    pub fn look(&self, key: &str) -> Look {
+        self.looks.get(key).cloned().unwrap_or(Look {
+            glyphs: vec!['?'],
+            color: Color::White,
+            accent: Color::White,
+            bold: false,
+        })
+    }
+}
+
+pub fn look(world: &World, key: &str) -> Look {
+    match world.resource::<Theme>() {
+        Some(theme) => theme.look(key),
+        None => classic().look(key),
+    }
+}
+
+// For looks wanted before the ThemeSwitcher has started
+fn classic() -> &'static Theme {
+    static CLASSIC_THEME: OnceLock<Theme> = OnceLock::new();
+    CLASSIC_THEME.get_or_init(Theme::classic)
+}
+
+// What an animated entity is, so its Animator can be rebuilt in the new
+// colors when the theme changes
+#[derive(Serialize, Deserialize)]
+pub struct Kind(pub String);
+
+// Sets the Theme resource and cycles through the themes on 't'
+pub struct ThemeSwitcher {
+    themes: Vec<Theme>,
+    current: usize,
+    applied: bool,
+}
+
+impl ThemeSwitcher {
+    pub fn new(themes: Vec<Theme>, current: usize) -> Self {
+        ThemeSwitcher {
+            themes,
+            current,
+            applied: false,
+        }
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.themes[self.current]
+    }
+
+    fn apply(&mut self, world: &mut World) {
+        self.applied = true;
+        let theme = self.themes[self.current].clone();
+        for entity in world.with_component::<Kind>() {
+            let kind = world.get_component::<Kind>(entity).unwrap().0.clone();
+            let look = theme.look(&format!("actors.{}", kind));
+            let Some(mut animator) = sprites::animator(&kind, &look) else {
+                continue;
+            };
+            if let Some(old) = world.get_component::<Animator>(entity) {
+                animator.play(old.current());
+            }
+            world.set_component(entity, animator);
+        }
+        world.set_resource(theme);
+    }
+}
+
+impl Entity for ThemeSwitcher {
+    fn start(&mut self, world: &mut World, _id: i64) {
+        self.apply(world);
+    }
+    fn update(&mut self, _delta: f64, world: &mut World, _id: i64) {
+        if world.ui.current_input == Some(KeyCode::Char('t')) {
+            self.current = (self.current + 1) % self.themes.len();
+            log::info!("switched to the {} theme", self.theme().name);
+            self.apply(world);
+        } else if !self.applied {
+            // made by a load, which leaves out the animators
+            self.apply(world);
+        }
+    }
+}
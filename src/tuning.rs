@@ -0,0 +1,318 @@
+use crate::engine::{Entity, World};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use toml::Table;
+
+const POLL_INTERVAL: f64 = 0.5; // seconds between looks at the file
+
+// The numbers the game plays by, with tuning.toml on top of the defaults
+// here. A resource kept by the Tuner, which reloads it when the file
+// changes and the console can set.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tuning {
+    pub bullet_speed: f64,
+    pub player_speed: f64,
+    pub player_reload_time: f64,
+    pub player_lives: f64,
+    pub plibble_speed: f64,
+    pub plibble_reload_time: f64,
+    pub plibbler_reload_time: f64,
+    pub plibbler_speed: f64,
+    pub shootler_speed: f64,
+    pub shootler_reload_time: f64,
+    pub bomb_speed: f64,
+    pub plibbler_bomb_reload_time: f64,
+    pub enemy_fire_interval: f64,
+    pub max_enemy_bullets: usize,
+    pub aimed_shot_chance: f64,
+    pub power_up_drop_chance: f64,
+    pub power_up_speed: f64,
+    pub power_up_duration: f64,
+    pub rapid_fire_reload_time: f64,
+    pub spread_drift: f64,
+    pub zigzag_speed: f64,
+    pub zigzag_drift: f64,
+    pub zigzag_period: f64,
+    pub homing_speed: f64,
+    pub homing_turn_rate: f64,
+    pub laser_duration: f64,
+    pub wave_delay: f64,
+    pub boss_wave_interval: u32,
+    pub boss_hp: f64,
+    pub boss_speeds: [f64; 3],
+    pub boss_attack_intervals: [f64; 3],
+}
+
+// Speeds are in characters per second, times in seconds
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            player_speed: 4.5,
+            player_reload_time: 0.3,
+            player_lives: 3.0,
+            bullet_speed: 5.0,
+            plibble_speed: 2.0,
+            plibble_reload_time: 6.0,
+            plibbler_speed: 1.5,
+            plibbler_reload_time: 3.0,
+            plibbler_bomb_reload_time: 5.0,
+            shootler_speed: 1.0,
+            shootler_reload_time: 2.0,
+            enemy_fire_interval: 0.8,
+            max_enemy_bullets: 3,
+            aimed_shot_chance: 0.6,
+            bomb_speed: 3.0,
+            zigzag_speed: 4.0,
+            zigzag_drift: 0.75,
+            zigzag_period: 0.4,
+            homing_speed: 4.0,
+            homing_turn_rate: 2.0,
+            laser_duration: 0.6,
+            power_up_drop_chance: 0.25,
+            power_up_speed: 2.0,
+            power_up_duration: 8.0,
+            rapid_fire_reload_time: 0.12,
+            spread_drift: 0.5,
+            wave_delay: 2.0,
+            boss_wave_interval: 3,
+            boss_hp: 30.0,
+            boss_speeds: [1.5, 2.0, 3.0],
+            boss_attack_intervals: [2.0, 1.6, 2.4],
+        }
+    }
+}
+
+impl Tuning {
+    // Values the text leaves out keep their defaults
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let file: Table = text.parse().map_err(|x: toml::de::Error| {
+            // the first and last lines, without the snippet in between
+            let message = x.to_string();
+            let mut lines = message.lines().filter(|x| !x.trim().is_empty());
+            let first = lines.next().unwrap_or_default().to_string();
+            match lines.next_back() {
+                Some(last) => format!("{}: {}", first, last),
+                None => first,
+            }
+        })?;
+        Tuning::over_defaults(file)
+    }
+
+    // All of it on one line, for a replay to set the game up with
+    pub fn line(&self) -> String {
+        let values: Vec<String> = self
+            .table()
+            .iter()
+            .map(|(key, value)| format!("{} = {}", key, value))
+            .collect();
+        format!("{{ {} }}", values.join(", "))
+    }
+
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        let file = format!("tuning = {}", line)
+            .parse::<Table>()
+            .ok()
+            .and_then(|mut x| x.remove("tuning"))
+            .and_then(|x| x.try_into::<Table>().ok())
+            .ok_or(format!("{} is not a table", line))?;
+        Tuning::over_defaults(file)
+    }
+
+    fn over_defaults(file: Table) -> Result<Self, String> {
+        let mut table = Tuning::default().table();
+        for (key, value) in file {
+            if !table.contains_key(&key) {
+                return Err(format!("unknown value {}", key));
+            }
+            table.insert(key, value);
+        }
+        Tuning::from_table(table)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|x| format!("{}: {}", path.display(), x))?;
+        Tuning::parse(&text).map_err(|x| format!("{}: {}", path.display(), x))
+    }
+
+    fn table(&self) -> Table {
+        Table::try_from(self).unwrap_or_default()
+    }
+
+    fn from_table(table: Table) -> Result<Self, String> {
+        let tuning: Tuning =
+            Table::try_into(table).map_err(|x| x.message().to_string())?;
+        tuning.check()?;
+        Ok(tuning)
+    }
+
+    // Anything that would divide by zero, run time backwards or never
+    // happen is turned away
+    fn check(&self) -> Result<(), String> {
+        for (key, value) in self.table() {
+            let numbers = match value {
+                toml::Value::Array(x) => x,
+                x => vec![x],
+            };
+            for number in numbers {
+                let number = number.as_float().unwrap_or(0.0);
+                if !number.is_finite() || number < 0.0 {
+                    return Err(format!("{} can't be negative", key));
+                }
+            }
+        }
+        let chances = [
+            ("aimed_shot_chance", self.aimed_shot_chance),
+            ("power_up_drop_chance", self.power_up_drop_chance),
+        ];
+        if let Some((key, _)) = chances.iter().find(|(_, x)| *x > 1.0) {
+            return Err(format!("{} goes from 0 to 1", key));
+        }
+        let positive = [
+            ("player_lives", self.player_lives),
+            ("zigzag_period", self.zigzag_period),
+            ("boss_hp", self.boss_hp),
+            ("boss_wave_interval", self.boss_wave_interval as f64),
+        ];
+        if let Some((key, _)) = positive.iter().find(|(_, x)| *x <= 0.0) {
+            return Err(format!("{} has to be more than 0", key));
+        }
+        Ok(())
+    }
+
+    // Written the way the constants used to be, like PLAYER_SPEED
+    pub fn names(&self) -> Vec<String> {
+        self.table().keys().map(|x| x.to_uppercase()).collect()
+    }
+
+    pub fn value(&self, name: &str) -> Option<String> {
+        Some(self.table().get(&name.to_lowercase())?.to_string())
+    }
+
+    // `value` is TOML, so `set BOSS_SPEEDS [1, 2, 4]` works too
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let key = name.to_lowercase();
+        let mut table = self.table();
+        if !table.contains_key(&key) {
+            return Err(format!("no such value {}", name));
+        }
+        let new = format!("value = {}", value)
+            .parse::<Table>()
+            .ok()
+            .and_then(|mut x| x.remove("value"))
+            .ok_or(format!("{} is not a number or list", value))?;
+        table.insert(key, new);
+        *self = Tuning::from_table(table)?;
+        Ok(())
+    }
+}
+
+// The defaults before the Tuner has started
+pub fn get(world: &World) -> Tuning {
+    world.resource::<Tuning>().copied().unwrap_or_default()
+}
+
+// What is wrong with the tuning file, for the HUD. The last good values
+// stay in play until it is fixed.
+pub struct TuningError(pub String);
+
+// Keeps the Tuning resource in step with the file
+#[derive(Serialize, Deserialize)]
+pub struct Tuner {
+    path: Option<PathBuf>,
+    #[serde(skip)]
+    tuning: Tuning,
+    #[serde(skip)]
+    error: Option<String>,
+    #[serde(skip)]
+    read: bool,
+    #[serde(skip)]
+    modified: Option<SystemTime>,
+    #[serde(skip)]
+    poll: f64,
+}
+
+impl Tuner {
+    // Without a file the defaults are used
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let mut tuner = Tuner {
+            path,
+            tuning: Tuning::default(),
+            error: None,
+            read: false,
+            modified: None,
+            poll: 0.0,
+        };
+        tuner.reload();
+        tuner
+    }
+
+    // For replays, which have to play by the values they were recorded with
+    pub fn fixed(tuning: Tuning) -> Self {
+        let mut tuner = Tuner::new(None);
+        tuner.tuning = tuning;
+        tuner
+    }
+
+    pub fn tuning(&self) -> &Tuning {
+        &self.tuning
+    }
+
+    // Whether the file changed since it was last read
+    fn reload(&mut self) -> bool {
+        let Some(path) = &self.path else {
+            return false;
+        };
+        let modified = fs::metadata(path).and_then(|x| x.modified()).ok();
+        if self.read && modified == self.modified {
+            return false;
+        }
+        self.read = true;
+        self.modified = modified;
+        match Tuning::load(path) {
+            Ok(tuning) => {
+                log::info!("tuning loaded from {}", path.display());
+                self.tuning = tuning;
+                self.error = None;
+            }
+            Err(error) => {
+                log::warn!("{}", error);
+                self.error = Some(error);
+            }
+        }
+        true
+    }
+
+    fn apply(&self, world: &mut World, id: i64) {
+        world.set_resource(self.tuning);
+        match &self.error {
+            Some(error) => world.set_component(id, TuningError(error.clone())),
+            None => world.remove_component::<TuningError>(id),
+        }
+    }
+}
+
+impl Entity for Tuner {
+    fn start(&mut self, world: &mut World, id: i64) {
+        self.apply(world, id);
+    }
+    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
+        // the replay keeps the values from when it started
+        if world.replaying() {
+            return;
+        }
+        self.poll -= delta;
+        if self.poll > 0.0 {
+            return;
+        }
+        self.poll = POLL_INTERVAL;
+        if self.reload() {
+            self.apply(world, id);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::banner::Banner;
+use crate::boss::Boss;
+use crate::engine::{Entity, World};
+use crate::layout::Layout;
+use crate::theme;
+use crate::tuning;
+use crate::{
+    Align, Alignment, EnemyMotion, Plibble, Plibbler, Shootler,
+    WAVE_BANNER_TIME, WAVE_FADE_TIME,
+};
+use core::mem::discriminant as tag;
+use serde::{Deserialize, Serialize};
+
+// Current wave number, readable by the HUD
+#[derive(Serialize, Deserialize)]
+pub struct Wave {
+    pub number: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WaveDirector {
+    pub delay: f64,
+    pub spawned: Vec<i64>,
+}
+
+impl WaveDirector {
+    pub fn new() -> Self {
+        WaveDirector {
+            delay: 0.0,
+            spawned: vec![],
+        }
+    }
+
+    fn cleared(&mut self, world: &mut World) -> bool {
+        // entities added this tick have no components until they start
+        self.spawned.retain(|x| world.is_alive(*x));
+        let enemies_left =
+            world.with_component::<Align>().into_iter().any(|x| {
+                world.get_component::<Align>(x).is_some_and(|x| {
+                    tag(&x.alignment) == tag(&Alignment::Enemy)
+                })
+            });
+        !enemies_left && self.spawned.is_empty()
+    }
+}
+
+impl Entity for WaveDirector {
+    fn start(&mut self, world: &mut World, id: i64) {
+        world.set_component(id, Wave { number: 0 });
+    }
+    fn update(&mut self, delta: f64, world: &mut World, id: i64) {
+        if !self.cleared(world) {
+            self.delay = tuning::get(world).wave_delay;
+            return;
+        }
+        // the boss leaves its frenzy tint behind
+        world.post.tint = None;
+        if self.delay > 0.0 {
+            self.delay -= delta;
+            // the end of the break goes dark for the next wave to fade in
+            if self.delay <= WAVE_FADE_TIME {
+                world.post.fade_out(WAVE_FADE_TIME);
+            }
+            return;
+        }
+        let number = match world.get_component::<Wave>(id) {
+            Some(wave) => {
+                wave.number += 1;
+                wave.number
+            }
+            None => return,
+        };
+        self.spawned = spawn_wave(world, number);
+        // the first wave comes in with the game's own fade
+        if number > 1 {
+            world.post.fade_in(WAVE_FADE_TIME);
+        }
+        log::info!("wave {} with {} enemies", number, self.spawned.len());
+        let look = theme::look(world, "hud.wave_banner");
+        world.add_entity(
+            Banner::new(&format!("WAVE\n{}", number), look.style())
+                .lasting(WAVE_BANNER_TIME),
+        );
+    }
+}
+
+fn spawn_wave(world: &mut World, number: u32) -> Vec<i64> {
+    let layout = Layout::of(world);
+    let tuning = tuning::get(world);
+    let interval = tuning.boss_wave_interval.max(1);
+    if number % interval == 0 {
+        return vec![world.add_entity(Boss::new(layout.at((10, 2))))];
+    }
+
+    let mut spawned = vec![
+        world.add_entity(Plibbler {
+            motion: EnemyMotion {
+                position: layout.at((3, 1)),
+                tilt: (0.0, 0.0),
+                target: (1, 0),
+                bounds: layout.bounds((2, 10)),
+            },
+            reload: tuning.plibbler_reload_time,
+        }),
+        world.add_entity(Plibbler {
+            motion: EnemyMotion {
+                position: layout.at((21, 1)),
+                tilt: (0.0, 0.0),
+                target: (-1, 0),
+                bounds: layout.bounds((14, 22)),
+            },
+            reload: tuning.plibbler_reload_time,
+        }),
+        world.add_entity(Plibble {
+            motion: EnemyMotion {
+                position: layout.at((1, 2)),
+                tilt: (0.0, 0.0),
+                target: (1, 0),
+                bounds: layout.bounds((1, 11)),
+            },
+        }),
+        world.add_entity(Plibble {
+            motion: EnemyMotion {
+                position: layout.at((23, 2)),
+                tilt: (0.0, 0.0),
+                target: (-1, 0),
+                bounds: layout.bounds((13, 23)),
+            },
+        }),
+        world.add_entity(Shootler {
+            motion: EnemyMotion {
+                position: layout.at((22, 3)),
+                tilt: (0.0, 0.0),
+                target: (-1, 0),
+                bounds: layout.bounds((14, 22)),
+            },
+        }),
+    ];
+    // every regular wave past the first brings another Shootler, up to three
+    let extra = (number - number / interval - 1).min(3);
+    for i in 0..extra as u16 {
+        spawned.push(world.add_entity(Shootler {
+            motion: EnemyMotion {
+                position: layout.at((2 + i * 7, 4)),
+                tilt: (0.0, 0.0),
+                target: (1, 0),
+                bounds: layout.bounds((2, 22)),
+            },
+        }));
+    }
+    spawned
+}